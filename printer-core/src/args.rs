@@ -0,0 +1,165 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use syn::{parse::Parser, punctuated::Punctuated, Expr, Ident, Meta, MetaNameValue, Token};
+
+use crate::retries::parse_retries_arg;
+use crate::timeout::parse_duration_arg;
+
+/// Arguments passed to the printer macro itself, i.e. `#[printer(...)]`.
+#[derive(Clone, Debug, Default)]
+pub struct PrinterArgs {
+    pub(crate) property_hooks: PropertyHooks,
+    pub(crate) matrix: Vec<(Ident, Vec<Expr>)>,
+    /// Timeout of every test in the module in milliseconds, unless the test sets its own.
+    pub(crate) timeout: Option<u64>,
+    /// Number of retries of every test in the module, unless the test sets its own.
+    pub(crate) retries: Option<usize>,
+    /// Slow threshold of every test in the module in milliseconds, unless the test sets its own.
+    pub(crate) slow: Option<u64>,
+    /// Maximum number of the tests of the module running at the same time, `1` with `serial`.
+    pub(crate) max_parallel: Option<usize>,
+    /// Whether the tests of the module run one after another in the order of declaration.
+    pub(crate) sequential: bool,
+    /// Whether the tests of the module are skipped after the first failure.
+    pub(crate) bail: bool,
+    /// Whether the expansion of the module is written to `target/printer-expand`.
+    pub(crate) expand: bool,
+    /// Whether the tests are reported at runtime, with the `reporting` feature of `printer`. It is
+    /// not a macro argument, see [`printer_core_reporting`](crate::printer_core_reporting).
+    pub(crate) reporting: bool,
+}
+
+/// Decides where the hooks are inserted for the properties declared inside of `proptest!` and
+/// `quickcheck!` invocations.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PropertyHooks {
+    /// Hooks are inserted into the body of the property, so they run for every generated case.
+    #[default]
+    PerCase,
+    /// Hooks run once around the whole property, i.e. once per generated test function.
+    PerProperty,
+}
+
+impl PrinterArgs {
+    pub fn parse(args: TokenStream) -> Self {
+        let metas = match Punctuated::<Meta, Token![,]>::parse_terminated.parse2(args.clone()) {
+            Ok(metas) => metas,
+            Err(_e) => abort!(
+                args,
+                "printer macro arguments should be a list of `key = value` pairs or flags"
+            ),
+        };
+
+        let mut printer_args = Self::default();
+        let mut serial = false;
+        for meta in metas {
+            let key = meta.path().get_ident().map(|ident| ident.to_string());
+            match (key.as_deref(), &meta) {
+                (Some("property_hooks"), Meta::NameValue(name_value)) => {
+                    printer_args.property_hooks = match expr_ident(&name_value.value).as_deref() {
+                        Some("per_case") => PropertyHooks::PerCase,
+                        Some("per_property") => PropertyHooks::PerProperty,
+                        _ => abort!(
+                            name_value.value,
+                            "`property_hooks` should be either `per_case` or `per_property`"
+                        ),
+                    };
+                }
+                (Some("matrix"), Meta::List(list)) => {
+                    let variables = match list
+                        .parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+                    {
+                        Ok(variables) => variables,
+                        Err(e) => abort!(
+                            e.span(),
+                            "`matrix` should contain `name = [values]` pairs: {}",
+                            e
+                        ),
+                    };
+
+                    for variable in variables {
+                        let Some(name) = variable.path.get_ident() else {
+                            abort!(
+                                variable.path,
+                                "matrix variable name should be an identifier"
+                            );
+                        };
+                        let Expr::Array(values) = variable.value else {
+                            abort!(
+                                variable.value,
+                                "matrix values should be an array, e.g. `[alice, bob]`"
+                            );
+                        };
+                        if values.elems.is_empty() {
+                            abort!(
+                                values,
+                                "matrix variable `{}` should have at least one value",
+                                name
+                            );
+                        }
+
+                        printer_args
+                            .matrix
+                            .push((name.clone(), values.elems.into_iter().collect()));
+                    }
+                }
+                (Some("timeout"), Meta::NameValue(name_value)) => {
+                    printer_args.timeout = Some(parse_duration_arg("timeout", &name_value.value));
+                }
+                (Some("retries"), Meta::NameValue(name_value)) => {
+                    printer_args.retries = Some(parse_retries_arg(&name_value.value));
+                }
+                (Some("slow"), Meta::NameValue(name_value)) => {
+                    printer_args.slow = Some(parse_duration_arg("slow", &name_value.value));
+                }
+                (Some("sequential"), Meta::Path(_)) => printer_args.sequential = true,
+                (Some("bail"), Meta::Path(_)) => printer_args.bail = true,
+                (Some("expand"), Meta::Path(_)) => printer_args.expand = true,
+                (Some("serial"), Meta::Path(_)) => {
+                    if serial {
+                        abort!(meta, "duplicate `serial`");
+                    }
+                    if printer_args.max_parallel.is_some() {
+                        abort!(meta, "`serial` can't be used together with `max_parallel`");
+                    }
+                    serial = true;
+                    printer_args.max_parallel = Some(1);
+                }
+                (Some("max_parallel"), Meta::NameValue(name_value)) => {
+                    if serial {
+                        abort!(meta, "`max_parallel` can't be used together with `serial`");
+                    }
+                    if printer_args.max_parallel.is_some() {
+                        abort!(meta, "duplicate `max_parallel`");
+                    }
+                    let max_parallel = match &name_value.value {
+                        Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Int(lit),
+                            ..
+                        }) => lit.base10_parse::<usize>().ok(),
+                        _ => None,
+                    };
+                    match max_parallel {
+                        Some(max_parallel) if max_parallel > 0 => {
+                            printer_args.max_parallel = Some(max_parallel)
+                        }
+                        _ => abort!(
+                            name_value.value,
+                            "`max_parallel` should be a positive number, e.g. `max_parallel = 2`"
+                        ),
+                    }
+                }
+                _ => abort!(meta, "unknown printer macro argument"),
+            }
+        }
+
+        printer_args
+    }
+}
+
+fn expr_ident(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(expr_path) => expr_path.path.get_ident().map(|ident| ident.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,142 @@
+use proc_macro2::Span;
+use proc_macro_error::{abort_if_dirty, emit_error};
+use quote::quote_spanned;
+use syn::{parse_quote, Item, Stmt};
+
+/// Returns a statement that makes rustc emit a warning with the given message at `span`.
+///
+/// Proc macros can't emit warnings on stable Rust, hence the statement uses a deprecated constant
+/// and the message is shown by the `deprecated` lint.
+pub fn warning(span: Span, message: &str) -> Stmt {
+    let use_of_constant = quote_spanned! {span=>
+        PRINTER_WARNING
+    };
+
+    parse_quote! {
+        {
+            #[deprecated(note = #message)]
+            const PRINTER_WARNING: () = ();
+            #use_of_constant
+        };
+    }
+}
+
+/// Warnings and errors about the code of the hooks. The code of a hook is copied into every test,
+/// hence every diagnostic is emitted once and names the tests it concerns.
+#[derive(Default)]
+pub struct HookDiagnostics {
+    warnings: Vec<HookWarning>,
+    missing_params: Vec<MissingParam>,
+}
+
+struct HookWarning {
+    span: Span,
+    /// The attribute and the name of the hook, e.g. ``before_each `setup` ``.
+    hook: String,
+    message: String,
+    tests: Vec<String>,
+}
+
+/// A parameter of the tests which a hook uses, but some of the tests it is inserted into lack.
+struct MissingParam {
+    span: Span,
+    kind: String,
+    hook: String,
+    param: String,
+    tests: Vec<String>,
+}
+
+impl HookDiagnostics {
+    /// Warns with `message` at `span` in the code of the hook of the `kind` (e.g. `before_each`)
+    /// named `hook`, inserted into the `test`.
+    pub fn warn(&mut self, span: Span, kind: &str, hook: &str, message: &str, test: &str) {
+        let hook = format!("{} `{}`", kind, hook);
+        let existing = self.warnings.iter_mut().find(|warning| {
+            warning.hook == hook
+                && warning.message == message
+                && format!("{:?}", warning.span) == format!("{:?}", span)
+        });
+        match existing {
+            Some(warning) => {
+                if !warning.tests.iter().any(|warned| warned == test) {
+                    warning.tests.push(test.to_string());
+                }
+            }
+            None => self.warnings.push(HookWarning {
+                span,
+                hook,
+                message: message.to_string(),
+                tests: vec![test.to_string()],
+            }),
+        }
+    }
+
+    /// Reports that the hook of the `kind` named `hook` uses the `param` at `span`, which the `test`
+    /// doesn't have.
+    pub fn missing_param(&mut self, span: Span, kind: &str, hook: &str, param: &str, test: &str) {
+        let hook = format!("{} `{}`", kind, hook);
+        let existing = self
+            .missing_params
+            .iter_mut()
+            .find(|missing| missing.hook == hook && missing.param == param);
+        match existing {
+            Some(missing) => {
+                if !missing.tests.iter().any(|lacking| lacking == test) {
+                    missing.tests.push(test.to_string());
+                }
+            }
+            None => self.missing_params.push(MissingParam {
+                span,
+                kind: kind.to_string(),
+                hook,
+                param: param.to_string(),
+                tests: vec![test.to_string()],
+            }),
+        }
+    }
+
+    /// Emits the errors, aborting if there are any, and returns the items of the module emitting
+    /// the warnings.
+    pub fn into_items(self) -> Vec<Item> {
+        for missing in &self.missing_params {
+            emit_error!(
+                missing.span,
+                "{} uses `{}`, which is not a parameter of {}",
+                missing.hook,
+                missing.param,
+                tests_list(&missing.tests);
+                help = "add the `{}` parameter to the tests, or use `#[{}(only)]` to insert the hook only into the tests having the parameter", missing.param, missing.kind
+            );
+        }
+        if !self.missing_params.is_empty() {
+            abort_if_dirty();
+        }
+
+        self.warnings
+            .into_iter()
+            .map(|warning| {
+                let message = format!(
+                    "{} (in {}, inserted into {})",
+                    warning.message,
+                    warning.hook,
+                    tests_list(&warning.tests)
+                );
+                let warning = self::warning(warning.span, &message);
+                parse_quote! {
+                    const _: () = {
+                        #warning
+                    };
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns e.g. ``test `it_works` `` or ``tests `it_works`, `it_fails` ``.
+fn tests_list(tests: &[String]) -> String {
+    let names: Vec<String> = tests.iter().map(|test| format!("`{}`", test)).collect();
+    match names.len() {
+        1 => format!("test {}", names[0]),
+        _ => format!("tests {}", names.join(", ")),
+    }
+}
@@ -0,0 +1,77 @@
+use crate::utils::{count_test_cases, name_suffix, take_attr};
+use proc_macro_error::abort;
+use quote::format_ident;
+use syn::{parse_quote, punctuated::Punctuated, Expr, FnArg, ItemFn, Stmt, Token};
+
+/// Expands a test marked with `#[each(...)]` into a test per row, returned with the `let` statements
+/// binding the row to the leading parameters of the test, which are inserted in front of the hooks.
+/// Other tests are returned as they are.
+pub fn expand_each(mut func: ItemFn) -> Vec<(ItemFn, Vec<Stmt>)> {
+    let Some(each_attr) = take_attr(&mut func.attrs, "each") else {
+        return vec![(func, vec![])];
+    };
+    if count_test_cases(&func).is_none() {
+        abort!(each_attr, "`each` should be used on test functions");
+    }
+
+    let rows = match each_attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+        Ok(rows) => rows,
+        Err(e) => abort!(e.span(), "`each` should contain a list of rows: {}", e),
+    };
+    if rows.is_empty() {
+        abort!(each_attr, "`each` should contain at least one row");
+    }
+
+    let mut names: Vec<String> = vec![];
+    rows.into_iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            let values: Vec<Expr> = match row {
+                Expr::Tuple(tuple) if tuple.elems.len() > 1 => tuple.elems.into_iter().collect(),
+                row => vec![row],
+            };
+            if values.len() > func.sig.inputs.len() {
+                abort!(
+                    each_attr,
+                    "row {} of `each` has {} values, but `{}` takes only {} parameters",
+                    idx + 1,
+                    values.len(),
+                    func.sig.ident,
+                    func.sig.inputs.len()
+                );
+            }
+
+            let mut test = func.clone();
+            let mut name = format!("{}_{}", func.sig.ident, name_suffix(&values));
+            if names.contains(&name) {
+                name = format!("{}_{}", name, idx + 1);
+            }
+            test.sig.ident = format_ident!("{}", name, span = func.sig.ident.span());
+            names.push(name);
+
+            let params: Vec<FnArg> = test.sig.inputs.iter().take(values.len()).cloned().collect();
+            test.sig.inputs = test.sig.inputs.into_iter().skip(values.len()).collect();
+
+            let bindings: Vec<Stmt> = params
+                .into_iter()
+                .zip(values)
+                .map(|(param, value)| -> Stmt {
+                    match param {
+                        FnArg::Typed(pat_type) => {
+                            let pat = &pat_type.pat;
+                            let ty = &pat_type.ty;
+                            parse_quote! {
+                                let #pat: #ty = #value;
+                            }
+                        }
+                        FnArg::Receiver(receiver) => {
+                            abort!(receiver, "`each` can't bind values to `self`")
+                        }
+                    }
+                })
+                .collect();
+
+            (test, bindings)
+        })
+        .collect()
+}
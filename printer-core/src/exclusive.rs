@@ -0,0 +1,49 @@
+use proc_macro_error::abort;
+use syn::{parse_quote, punctuated::Punctuated, Ident, ItemFn, Token};
+
+/// Removes all of the `#[exclusive(...)]` attributes and returns the names of the resources they
+/// list.
+pub fn take_exclusive(attrs: &mut Vec<syn::Attribute>) -> Vec<String> {
+    let mut resources = vec![];
+    attrs.retain(|attr| {
+        if !attr.path().is_ident("exclusive") {
+            return true;
+        }
+
+        match attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated) {
+            Ok(names) if !names.is_empty() => {
+                resources.extend(names.iter().map(|name| name.to_string()))
+            }
+            Ok(_) => abort!(attr, "`exclusive` should list at least one resource"),
+            Err(e) => abort!(
+                e.span(),
+                "`exclusive` should contain a list of resource names, e.g. `#[exclusive(node)]`: {}",
+                e
+            ),
+        }
+        false
+    });
+
+    resources
+}
+
+/// Makes the function lock the resources before anything else. The resources are sorted, so that
+/// every test acquires them in the same order.
+pub fn insert_resource_locks<'a>(
+    function: &mut ItemFn,
+    resources: impl Iterator<Item = &'a String>,
+) {
+    let mut resources: Vec<&String> = resources.collect();
+    resources.sort();
+    resources.dedup();
+    if resources.is_empty() {
+        return;
+    }
+
+    function.block.stmts.insert(
+        0,
+        parse_quote! {
+            let _printer_resources = printer::runtime::lock_resources(&[#(#resources),*]);
+        },
+    );
+}
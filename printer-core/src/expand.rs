@@ -0,0 +1,126 @@
+use std::path::{Component, Path, PathBuf};
+
+use syn::ItemMod;
+
+/// Returns true if the expansion of the modules is written for every module, i.e.
+/// `PRINTER_EXPAND=1`.
+pub fn expand_from_env() -> bool {
+    std::env::var("PRINTER_EXPAND").as_deref() == Ok("1")
+}
+
+/// Writes the pretty-printed expansion of the module to
+/// `target/printer-expand/<crate>/<file>/<module>-<line>.rs`, see [`expansion_path`]. `events` tells
+/// whether the compiled module keeps the events marking the hooks, see [`render_expansion`].
+///
+/// Failing to write the file doesn't fail the build, the error is printed instead.
+pub fn write_expansion(module: &ItemMod, events: bool) {
+    let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_else(|_| "crate".to_string());
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let path = target_dir().join("printer-expand").join(expansion_path(
+        &crate_name,
+        &manifest_dir,
+        module,
+    ));
+
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, render_expansion(module, events)));
+    if let Err(e) = result {
+        eprintln!(
+            "warning: failed to write the expansion of the module `{}` to {}: {}",
+            module.ident,
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Returns the path of the expansion of the module, relative to `target/printer-expand`. A proc
+/// macro doesn't know the path of the module, so the expansion is named after the source file of
+/// the module (without the `.rs` extension, relative to the manifest of the crate) and the line it
+/// is declared at, e.g. `flipper/tests/e2e/tests-12.rs` for the `tests` module declared at line 12
+/// of `tests/e2e.rs`. Without the source file, it is named after the crate only.
+pub fn expansion_path(crate_name: &str, manifest_dir: &Path, module: &ItemMod) -> PathBuf {
+    let span = module.ident.span();
+    let mut path = PathBuf::from(crate_name);
+    if let Some(file) = span.local_file() {
+        let file = file.strip_prefix(manifest_dir).unwrap_or(&file);
+        path.extend(
+            file.with_extension("")
+                .components()
+                .filter(|component| matches!(component, Component::Normal(_))),
+        );
+    }
+
+    path.join(format!("{}-{}.rs", module.ident, span.start().line))
+}
+
+/// Returns the pretty-printed module with comments around the code inserted from the hooks, which is
+/// found by the events marking it. The events are left out unless `events` is true, i.e. unless
+/// they are compiled as well.
+pub fn render_expansion(module: &ItemMod, events: bool) -> String {
+    let file = syn::File {
+        shebang: None,
+        attrs: vec![],
+        items: vec![syn::Item::Mod(module.clone())],
+    };
+    let code = prettyplease::unparse(&file);
+
+    // syn doesn't keep comments, so the hooks are found by the calls marking their start and end
+    let mut annotated = String::with_capacity(code.len());
+    // the hooks can be nested, e.g. the `after_each` hooks run before skipping in a `before_each`
+    let mut hooks = vec![];
+    for line in code.lines() {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let statement = line.trim();
+        if let Some(args) = statement
+            .strip_prefix("printer::runtime::hook_started(")
+            .and_then(|args| args.strip_suffix(");"))
+        {
+            let label = hook_label(args);
+            annotated.push_str(&format!("{}// start of {}\n", indent, label));
+            hooks.push(label);
+            if !events {
+                continue;
+            }
+        }
+        if statement == "printer::runtime::hook_finished();" {
+            if events {
+                annotated.push_str(line);
+                annotated.push('\n');
+            }
+            if let Some(label) = hooks.pop() {
+                annotated.push_str(&format!("{}// end of {}\n", indent, label));
+            }
+            continue;
+        }
+        annotated.push_str(line);
+        annotated.push('\n');
+    }
+
+    annotated
+}
+
+/// Returns e.g. `"before each" hook: setup` from the arguments `"before each", "setup"`.
+fn hook_label(args: &str) -> String {
+    match args.split_once(", ") {
+        Some((kind, name)) => format!("{} hook: {}", kind, name.trim_matches('"')),
+        None => format!("hook {}", args),
+    }
+}
+
+/// Returns the target directory of the crate being compiled, i.e. `CARGO_TARGET_DIR` or the
+/// closest `target` directory above the manifest of the crate.
+fn target_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("CARGO_TARGET_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default());
+    manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("target"))
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| manifest_dir.join("target"))
+}
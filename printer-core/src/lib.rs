@@ -1,25 +1,92 @@
 #![doc = include_str!("../README.md")]
 
+mod args;
+mod diagnostics;
+mod each;
+mod exclusive;
+mod expand;
+mod lint;
+mod matrix;
+mod params;
+mod pending;
+mod property;
+mod retries;
+mod sequence;
+mod skip;
+mod slow;
+mod suite;
 mod tests;
+mod timeout;
 mod utils;
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use proc_macro_error::abort;
 
-use syn::{parse2, parse_quote, ItemFn, ItemMod, ItemStatic, ItemUse, Stmt};
-use utils::{FunctionAttributeVariant, HooksData};
+use args::PrinterArgs;
+use diagnostics::{warning, HookDiagnostics};
+use each::expand_each;
+use exclusive::{insert_resource_locks, take_exclusive};
+use expand::{expand_from_env, write_expansion};
+use lint::{allow_unused_bindings, lint_hooks, take_shadow_allowed};
+use matrix::matrix_combinations;
+use params::{hooks_for_test, is_only_hook, test_params};
+use pending::{declare_todo, is_pending, take_pending_reason};
+use property::{expand_property_macro, PropertyMacroVariant};
+use retries::{insert_retries, take_retries};
+use sequence::{insert_completions, take_depends_on};
+use skip::rewrite_skips;
+use slow::{insert_body_markers, insert_slow_timer};
+use suite::{insert_test_guard, suite_static};
+use syn::spanned::Spanned;
+use syn::{
+    parse2, parse_quote, parse_quote_spanned, Ident, ItemFn, ItemMod, ItemStatic, ItemUse, Stmt,
+};
+use timeout::{insert_timeout, take_duration_attr};
+use utils::{
+    count_test_cases, take_attr, FunctionAttributeVariant, Hook, HooksData, PrinterAttributeVariant,
+};
 
 pub fn printer_core(args: TokenStream, input: TokenStream) -> TokenStream {
-    if !args.is_empty() {
-        abort!(args, "printer macro does not take any arguments");
-    }
+    expand_module(PrinterArgs::parse(args), input)
+}
+
+/// Like [`printer_core`], for the `reporting` feature of `printer`. Every test enters the suite of
+/// its module, which filters it with `PRINTER_GREP` and reports it, and the hooks are surrounded
+/// with the events timing them.
+pub fn printer_core_reporting(args: TokenStream, input: TokenStream) -> TokenStream {
+    let printer_args = PrinterArgs {
+        reporting: true,
+        ..PrinterArgs::parse(args)
+    };
+    expand_module(printer_args, input)
+}
 
-    let mut mod_item = match parse2::<ItemMod>(input.clone()) {
+fn expand_module(printer_args: PrinterArgs, input: TokenStream) -> TokenStream {
+    let mod_item = match parse2::<ItemMod>(input.clone()) {
         Ok(item) => item,
         Err(_e) => abort!(input, "printer macro should be used on mod with tests"),
     };
 
-    let hooks_data = get_hooks_from_mod(&mut mod_item);
+    let expanded = expand_mod_item(mod_item.clone(), &printer_args, printer_args.reporting);
+    // the written expansion marks the code of the hooks, without changing the compiled module
+    if printer_args.expand || expand_from_env() {
+        if printer_args.reporting {
+            write_expansion(&expanded, true);
+        } else {
+            write_expansion(&expand_mod_item(mod_item, &printer_args, true), false);
+        }
+    }
+
+    parse_quote! {
+        #expanded
+    }
+}
+
+/// Inserts the hooks into the tests of the module, surrounded with the events marking them with
+/// `events`.
+fn expand_mod_item(mut mod_item: ItemMod, printer_args: &PrinterArgs, events: bool) -> ItemMod {
+    let mut hooks_data = get_hooks_from_mod(&mut mod_item);
+    hooks_data.events = events;
 
     // removing the printer-related code so that the project will compile
     mod_item
@@ -29,59 +96,272 @@ pub fn printer_core(args: TokenStream, input: TokenStream) -> TokenStream {
         .1
         .retain(|member| match member {
             syn::Item::Fn(func) => {
-                let func_attrs = &func.attrs;
-                if let Some(attr) = func_attrs.get(0) {
-                    match &attr.meta {
-                        syn::Meta::Path(path) => {
-                            let ident = path.segments[0].ident.to_string();
-                            match FunctionAttributeVariant::from(ident) {
-                                FunctionAttributeVariant::Printer(_) => false,
-                                FunctionAttributeVariant::External => true,
-                            }
-                        }
-                        _ => true,
-                    }
-                } else {
-                    true
-                }
+                FunctionAttributeVariant::from_attrs(&func.attrs)
+                    == FunctionAttributeVariant::External
             }
             _ => true,
         });
 
-    let mut test_cases_num: usize = 0;
+    let mut diagnostics = HookDiagnostics::default();
     let (_, item_vec) = mod_item.content.as_mut().unwrap();
-    for mut item in item_vec {
-        match &mut item {
-            syn::Item::Fn(func) => {
-                for attribute in &func.attrs.clone() {
-                    match attribute.meta.clone() {
-                        syn::Meta::Path(path) => {
-                            let last = &path.segments[&path.segments.len() - 1].ident.to_string();
-                            if last == "test" {
-                                test_cases_num += 1;
-                                let return_type_is_union = {
-                                    match &func.sig.output {
-                                        syn::ReturnType::Default => true,
-                                        syn::ReturnType::Type(_, _) => false, // might need additional logic
-                                    }
-                                };
-
-                                insert_code_end(func, &hooks_data.invariants, return_type_is_union);
-                                insert_code_front(func, &hooks_data.invariants);
-
-                                insert_code_front(func, &hooks_data.before_each);
-                                insert_code_end(func, &hooks_data.after_each, return_type_is_union);
-
-                                if !hooks_data.before_all.is_empty() {
-                                    insert_syncing_code(func);
-                                }
-                            }
+    let items: Vec<syn::Item> = std::mem::take(item_vec)
+        .into_iter()
+        .map(declare_todo)
+        .collect();
+    if printer_args.matrix.is_empty() {
+        *item_vec = expand_tests(items, &hooks_data, printer_args, &[], &mut diagnostics);
+    } else {
+        let (tests, others): (Vec<_>, Vec<_>) = items.into_iter().partition(is_test_item);
+        *item_vec = others;
+        for (mod_name, bindings) in matrix_combinations(&printer_args.matrix) {
+            let tests = expand_tests(
+                tests.clone(),
+                &hooks_data,
+                printer_args,
+                &bindings,
+                &mut diagnostics,
+            );
+            item_vec.push(parse_quote! {
+                mod #mod_name {
+                    use super::*;
+
+                    #(#tests)*
+                }
+            });
+        }
+    }
+    item_vec.extend(diagnostics.into_items());
+
+    // the tests of the matrix submodules share the semaphore of the module via `use super::*`
+    if let Some(max_parallel) = printer_args.max_parallel {
+        item_vec.insert(
+            0,
+            parse_quote! {
+                static PRINTER_SEMAPHORE: printer::runtime::Semaphore =
+                    printer::runtime::Semaphore::new(#max_parallel);
+            },
+        );
+    }
+
+    mod_item
+}
+
+/// Inserts the hooks into every test in `items` and adds the `before_all` test, if needed.
+/// `bindings` are inserted in front of the hooks of every test (and `before_all`), the warnings
+/// about the hooks are collected in `diagnostics`.
+fn expand_tests(
+    items: Vec<syn::Item>,
+    hooks_data: &HooksData,
+    printer_args: &PrinterArgs,
+    bindings: &[Stmt],
+    diagnostics: &mut HookDiagnostics,
+) -> Vec<syn::Item> {
+    // with `#[only]` on any of the tests, all of the other tests are ignored
+    let focused = items.iter().any(|item| match item {
+        syn::Item::Fn(func) => func.attrs.iter().any(|attr| attr.path().is_ident("only")),
+        _ => false,
+    });
+    let params = test_params(&items);
+
+    let mut test_cases_num: usize = 0;
+    let mut item_vec: Vec<syn::Item> = vec![];
+    // positions of the tests in `item_vec` that are not ignored and their number of cases
+    let mut running_tests: Vec<(usize, usize)> = vec![];
+    let mut pending: Vec<(String, String)> = vec![];
+    // declared names and dependencies of the running tests, the names of the other tests
+    let mut declared: Vec<(String, Vec<Ident>)> = vec![];
+    let mut not_running: Vec<String> = vec![];
+    let mut uses_skip = false;
+    for item in items {
+        match item {
+            syn::Item::Fn(mut func) => {
+                let pending_reason = take_pending_reason(&mut func);
+                let only_attr = take_attr(&mut func.attrs, "only");
+                let timeout = take_duration_attr(&mut func, "timeout").or(printer_args.timeout);
+                let slow = take_duration_attr(&mut func, "slow").or(printer_args.slow);
+                let retries = take_retries(&mut func).or(printer_args.retries);
+                if let Some(exclusive_attr) = func
+                    .attrs
+                    .iter()
+                    .find(|attr| attr.path().is_ident("exclusive"))
+                {
+                    if count_test_cases(&func).is_none() {
+                        abort!(
+                            exclusive_attr,
+                            "`exclusive` should be used on test functions or hooks"
+                        );
+                    }
+                }
+                let exclusive = take_exclusive(&mut func.attrs);
+                let depends_on = take_depends_on(&mut func);
+                let shadow_allowed = take_shadow_allowed(&mut func);
+                let declared_name = func.sig.ident.to_string();
+                if only_attr.is_some() && count_test_cases(&func).is_none() {
+                    abort!(only_attr, "`only` should be used on test functions");
+                }
+                for (mut func, each_bindings) in expand_each(func) {
+                    if let Some(reason) = &pending_reason {
+                        insert_ignore(&mut func, reason);
+                        pending.push((func.sig.ident.to_string(), reason.clone()));
+                        push_names(&mut not_running, &declared_name, &func);
+                    } else if let Some(cases_num) = count_test_cases(&func) {
+                        let test_bindings = [bindings, &each_bindings].concat();
+                        let mut hooks_data = hooks_for_test(
+                            hooks_data,
+                            &func,
+                            &test_bindings,
+                            &params,
+                            &declared_name,
+                            diagnostics,
+                        );
+                        if slow.is_some() {
+                            insert_body_markers(&mut func);
+                        }
+                        lint_hooks(
+                            &hooks_data,
+                            &func,
+                            &shadow_allowed,
+                            &declared_name,
+                            diagnostics,
+                        );
+                        allow_unused_bindings(&mut hooks_data, &func);
+                        uses_skip |= insert_hooks(&mut func, &hooks_data);
+                        insert_code_front(&mut func, &[bindings.to_vec(), each_bindings]);
+                        if let Some(slow) = slow {
+                            insert_slow_timer(&mut func, slow);
+                        }
+                        if let Some(timeout) = timeout {
+                            insert_timeout(&mut func, timeout, &hooks_data);
+                        }
+                        if let Some(retries) = retries {
+                            insert_retries(&mut func, retries);
+                        }
+
+                        insert_resource_locks(
+                            &mut func,
+                            exclusive.iter().chain(&hooks_data.exclusive),
+                        );
+                        if printer_args.max_parallel.is_some() {
+                            func.block.stmts.insert(
+                                0,
+                                parse_quote! {
+                                    let _printer_permit = PRINTER_SEMAPHORE.acquire();
+                                },
+                            );
+                        }
+
+                        let defocused = focused && only_attr.is_none();
+                        if defocused {
+                            insert_ignore(&mut func, "not focused with #[only]");
+                            push_names(&mut not_running, &declared_name, &func);
+                        } else {
+                            test_cases_num += cases_num;
+                            running_tests.push((item_vec.len(), cases_num));
+                            declared.push((declared_name.clone(), depends_on.clone()));
+                        }
+                        if let Some(only_attr) = &only_attr {
+                            let warning = warning(
+                                only_attr.span(),
+                                "`#[only]` is set on this test, remove it before committing",
+                            );
+                            func.block.stmts.insert(0, warning);
+                        }
+
+                        // `before_all` doesn't send a message for the tests that are not focused,
+                        // which run only with `--include-ignored`
+                        if !hooks_data.before_all.is_empty() && !defocused {
+                            insert_syncing_code(&mut func);
                         }
-                        _ => {}
                     }
+                    item_vec.push(func.into());
+                }
+            }
+            syn::Item::Macro(item_macro) => {
+                if let Some(variant) = PropertyMacroVariant::try_from_macro(&item_macro) {
+                    let (items, cases_num) = expand_property_macro(
+                        &item_macro,
+                        variant,
+                        hooks_data,
+                        printer_args.property_hooks,
+                        bindings,
+                        focused,
+                    );
+                    test_cases_num += cases_num;
+                    item_vec.extend(items);
+                } else {
+                    item_vec.push(item_macro.into());
                 }
             }
-            _ => {}
+            item => item_vec.push(item),
+        }
+    }
+
+    // making the tests wait for their dependencies
+    let mut completions = vec![];
+    if let Some((completions_static, positions)) = insert_completions(
+        &mut item_vec,
+        &running_tests,
+        &declared,
+        &not_running,
+        printer_args.sequential,
+    ) {
+        completions = positions;
+        item_vec.insert(0, completions_static.into());
+        // `running_tests` hold the positions in `item_vec`
+        for (idx, _) in &mut running_tests {
+            *idx += 1;
+        }
+        for idx in &mut completions {
+            *idx += 1;
+        }
+    }
+
+    // adding the suite tracking the failures and printing the pending and skipped tests at the end
+    let reporting = printer_args.reporting;
+    let needs_suite = reporting
+        || printer_args.bail
+        || !pending.is_empty()
+        || uses_skip
+        || !completions.is_empty();
+    if needs_suite && (!running_tests.is_empty() || !pending.is_empty()) {
+        let mut tests = vec![];
+        for &(idx, cases_num) in &running_tests {
+            if let syn::Item::Fn(func) = &mut item_vec[idx] {
+                let ignored = func.attrs.iter().any(|attr| attr.path().is_ident("ignore"));
+                tests.push((func.sig.ident.to_string(), cases_num, ignored));
+                insert_test_guard(
+                    func,
+                    completions.contains(&idx),
+                    reporting,
+                    printer_args.bail,
+                );
+            }
+        }
+
+        // none of the tests of the module finishes, so a test of its own prints the summary
+        if running_tests.is_empty() {
+            item_vec.push(parse_quote! {
+                #[test]
+                fn printer_pending() {
+                    PRINTER_SUITE.finish_pending();
+                }
+            });
+        }
+
+        let suite_static: ItemStatic =
+            parse2(suite_static(&tests, &pending, printer_args.bail)).unwrap();
+        item_vec.insert(0, suite_static.into());
+    } else {
+        // the tests entering the suite check the environment when they enter it
+        for &(idx, _) in &running_tests {
+            if let syn::Item::Fn(func) = &mut item_vec[idx] {
+                func.block.stmts.insert(
+                    0,
+                    parse_quote! {
+                        printer::runtime::check_reporting_env();
+                    },
+                );
+            }
         }
     }
 
@@ -115,47 +395,45 @@ pub fn printer_core(args: TokenStream, input: TokenStream) -> TokenStream {
             }
         };
 
-        for (idx, code_line) in hooks_data.before_all.iter().enumerate() {
+        let before_all = hook_events("before all", &hooks_data.before_all, hooks_data.events);
+        for (idx, code_line) in bindings
+            .iter()
+            .chain(before_all.iter().flatten())
+            .enumerate()
+        {
             before_all_code
                 .block
                 .stmts
                 .insert(1 + idx, code_line.clone());
         }
 
-        mod_item
-            .content
-            .as_mut()
-            .unwrap()
-            .1
-            .insert(0, before_all_code.into());
-        mod_item
-            .content
-            .as_mut()
-            .unwrap()
-            .1
-            .insert(0, static_sync_channel.into());
-        mod_item
-            .content
-            .as_mut()
-            .unwrap()
-            .1
-            .insert(0, use_statement_crossbeam.into());
-        mod_item
-            .content
-            .as_mut()
-            .unwrap()
-            .1
-            .insert(0, use_statement_tokio.into());
-        mod_item
-            .content
-            .as_mut()
-            .unwrap()
-            .1
-            .insert(0, use_statement_once_cell.into());
+        insert_resource_locks(&mut before_all_code, hooks_data.before_all_exclusive.iter());
+
+        item_vec.insert(0, before_all_code.into());
+        item_vec.insert(0, static_sync_channel.into());
+        item_vec.insert(0, use_statement_crossbeam.into());
+        item_vec.insert(0, use_statement_tokio.into());
+        item_vec.insert(0, use_statement_once_cell.into());
     }
 
-    parse_quote! {
-        #mod_item
+    item_vec
+}
+
+/// Adds the name of the test, and the name it was declared with if `each` changed it.
+fn push_names(names: &mut Vec<String>, declared_name: &str, func: &ItemFn) {
+    for name in [declared_name.to_string(), func.sig.ident.to_string()] {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+}
+
+/// Returns true for the items [`expand_tests`] inserts the hooks into.
+fn is_test_item(item: &syn::Item) -> bool {
+    match item {
+        syn::Item::Fn(func) => count_test_cases(func).is_some() || is_pending(func),
+        syn::Item::Macro(item_macro) => PropertyMacroVariant::try_from_macro(item_macro).is_some(),
+        _ => false,
     }
 }
 
@@ -167,36 +445,106 @@ fn get_hooks_from_mod(mod_item: &mut ItemMod) -> HooksData {
         after_each: vec![],
         invariants: vec![],
         before_all: vec![],
+        exclusive: vec![],
+        before_all_exclusive: vec![],
+        events: false,
     };
 
     for item in vec_items {
-        match item {
-            syn::Item::Fn(func) => {
-                let function_attrs = func.attrs;
-                if let Some(attr) = function_attrs.get(0) {
-                    match &attr.meta {
-                        syn::Meta::Path(path) => {
-                            let ident = path.segments[0].ident.to_string();
-                            match FunctionAttributeVariant::from(ident) {
-                                FunctionAttributeVariant::Printer(printer_variant) => {
-                                    printer_variant
-                                        .populate_hooks_data(&mut hooks_data, func.block.stmts);
-                                }
-                                FunctionAttributeVariant::External => {}
-                            }
-                        }
-                        _ => {}
+        if let syn::Item::Fn(mut func) = item {
+            match FunctionAttributeVariant::from_attrs(&func.attrs) {
+                FunctionAttributeVariant::Printer(printer_variant) => {
+                    let exclusive = take_exclusive(&mut func.attrs);
+                    if printer_variant == PrinterAttributeVariant::BeforeAll {
+                        hooks_data.before_all_exclusive.extend(exclusive);
+                    } else {
+                        hooks_data.exclusive.extend(exclusive);
                     }
+                    let allowed = take_shadow_allowed(&mut func);
+                    let hook = Hook {
+                        name: func.sig.ident.to_string(),
+                        span: func.sig.ident.span(),
+                        allowed,
+                        only: is_only_hook(&func.attrs, &printer_variant),
+                        stmts: func.block.stmts,
+                    };
+                    printer_variant.populate_hooks_data(&mut hooks_data, hook);
                 }
+                FunctionAttributeVariant::External => {}
             }
-            _ => {}
         }
     }
 
     hooks_data
 }
 
-fn insert_code_front(function: &mut ItemFn, code_blocks: &Vec<Vec<Stmt>>) {
+/// Inserts the hooks into the test. Invocations of `skip!` in the test and in the `before_each` and
+/// `invariant` hooks are rewritten to run the remaining hooks before returning.
+///
+/// Returns true if the test (or its hooks) uses `skip!`.
+pub(crate) fn insert_hooks(function: &mut ItemFn, hooks_data: &HooksData) -> bool {
+    let return_type_is_union = {
+        match &function.sig.output {
+            syn::ReturnType::Default => true,
+            syn::ReturnType::Type(_, _) => false, // might need additional logic
+        }
+    };
+
+    let events = hooks_data.events;
+    let after_each = hook_events("after each", &hooks_data.after_each, events);
+    let end_invariants = hook_events("invariant", &hooks_data.invariants, events);
+    let end_hooks: Vec<Stmt> = end_invariants
+        .iter()
+        .chain(&after_each)
+        .flatten()
+        .cloned()
+        .collect();
+    let mut uses_skip = rewrite_skips(&mut function.block.stmts, &end_hooks);
+    let after_each_stmts = after_each.concat();
+    let mut invariants = hook_events("invariant", &hooks_data.invariants, events);
+    for invariant in &mut invariants {
+        uses_skip |= rewrite_skips(invariant, &after_each_stmts);
+    }
+    let mut before_each = hook_events("before each", &hooks_data.before_each, events);
+    for hook in &mut before_each {
+        uses_skip |= rewrite_skips(hook, &after_each_stmts);
+    }
+
+    insert_code_end(function, &end_invariants, return_type_is_union);
+    insert_code_front(function, &invariants);
+
+    insert_code_front(function, &before_each);
+    insert_code_end(function, &after_each, return_type_is_union);
+
+    uses_skip
+}
+
+/// Surrounds the code of every hook of the `kind` with the events timing it, unless `events` is
+/// false. The events are located at the hook, but keep the hygiene of the macro, so that lints like
+/// `unreachable_code` ignore them.
+fn hook_events(kind: &str, hooks: &[Hook], events: bool) -> Vec<Vec<Stmt>> {
+    hooks
+        .iter()
+        .map(|hook| {
+            if !events {
+                return hook.stmts.clone();
+            }
+
+            let name = &hook.name;
+            let span = Span::call_site().located_at(hook.span);
+            let mut stmts: Vec<Stmt> = vec![parse_quote_spanned! {span=>
+                printer::runtime::hook_started(#kind, #name);
+            }];
+            stmts.extend(hook.stmts.iter().cloned());
+            stmts.push(parse_quote_spanned! {span=>
+                printer::runtime::hook_finished();
+            });
+            stmts
+        })
+        .collect()
+}
+
+pub(crate) fn insert_code_front(function: &mut ItemFn, code_blocks: &[Vec<Stmt>]) {
     for code_block in code_blocks.iter().rev() {
         for code_line in code_block.iter().rev() {
             function.block.stmts.insert(0, code_line.clone());
@@ -204,9 +552,9 @@ fn insert_code_front(function: &mut ItemFn, code_blocks: &Vec<Vec<Stmt>>) {
     }
 }
 
-fn insert_code_end(
+pub(crate) fn insert_code_end(
     function: &mut ItemFn,
-    code_blocks: &Vec<Vec<Stmt>>,
+    code_blocks: &[Vec<Stmt>],
     return_type_is_union: bool,
 ) {
     for code_block in code_blocks.iter() {
@@ -223,8 +571,23 @@ fn insert_code_end(
     }
 }
 
-fn insert_syncing_code(function: &mut ItemFn) {
-    let syncing_code: Stmt = parse_quote! {
+/// Marks the test with `#[ignore = "<reason>"]`, unless it is already ignored.
+pub(crate) fn insert_ignore(function: &mut ItemFn, reason: &str) {
+    if !function
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("ignore"))
+    {
+        function.attrs.push(parse_quote! { #[ignore = #reason] });
+    }
+}
+
+pub(crate) fn insert_syncing_code(function: &mut ItemFn) {
+    function.block.stmts.insert(0, syncing_code());
+}
+
+pub(crate) fn syncing_code() -> Stmt {
+    parse_quote! {
         while true {
             if let Some(channel) = PRINTER_SYNC_CHANNEL.get() {
                 let this_receiver = channel.1.clone();
@@ -235,7 +598,5 @@ fn insert_syncing_code(function: &mut ItemFn) {
                 std::thread::sleep(std::time::Duration::from_millis(200));
             }
         }
-    };
-
-    function.block.stmts.insert(0, syncing_code);
+    }
 }
@@ -0,0 +1,406 @@
+use proc_macro2::{TokenStream, TokenTree};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{
+    parse_quote, Attribute, Expr, ExprAssign, ExprCall, ExprMethodCall, ExprPath, ExprReturn,
+    FnArg, Ident, Item, ItemFn, Local, Macro, Pat, Stmt, Token,
+};
+
+use crate::diagnostics::HookDiagnostics;
+use crate::utils::{Hook, HooksData};
+
+/// What `#[printer::allow(shadow)]` silences in a hook or in a test. `#[allow(printer::shadow)]`
+/// can't be supported, since rustc rejects the lints of unregistered tools before the expansion.
+#[derive(Clone, Debug, Default)]
+pub struct ShadowAllowed {
+    /// Whether it is set on the function, which silences all of the warnings about the function.
+    pub(crate) all: bool,
+    /// The bindings of the `let` statements it is set on.
+    pub(crate) bindings: Vec<String>,
+}
+
+impl ShadowAllowed {
+    fn allows(&self, binding: &str) -> bool {
+        self.all || self.bindings.iter().any(|allowed| allowed == binding)
+    }
+}
+
+/// Removes `#[printer::allow(shadow)]` from the function and from its `let` statements, and returns
+/// what it silences.
+pub fn take_shadow_allowed(func: &mut ItemFn) -> ShadowAllowed {
+    let mut allowed = ShadowAllowed {
+        all: take_allow_shadow(&mut func.attrs),
+        bindings: vec![],
+    };
+    for stmt in &mut func.block.stmts {
+        if let Stmt::Local(local) = stmt {
+            if take_allow_shadow(&mut local.attrs) {
+                allowed
+                    .bindings
+                    .extend(pat_idents(&local.pat).iter().map(Ident::to_string));
+            }
+        }
+    }
+
+    allowed
+}
+
+/// Removes `shadow` from the `#[printer::allow(...)]` attributes, returns true if it was there.
+fn take_allow_shadow(attrs: &mut Vec<Attribute>) -> bool {
+    let mut found = false;
+    attrs.retain_mut(|attr| {
+        if path_segments(attr.path()) != ["printer", "allow"] {
+            return true;
+        }
+        let Ok(lints) = attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+        else {
+            return true;
+        };
+        let (shadow, others): (Vec<_>, Vec<_>) = lints
+            .into_iter()
+            .partition(|path| path.is_ident("shadow"));
+        if shadow.is_empty() {
+            return true;
+        }
+
+        found = true;
+        if others.is_empty() {
+            return false;
+        }
+        let path = attr.path().clone();
+        *attr = parse_quote!(#[#path(#(#others),*)]);
+        true
+    });
+
+    found
+}
+
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect()
+}
+
+/// Silences the `unused_variables` lint of the `let` statements of the `before_each` and
+/// `invariant` hooks inserted into the `test` whose bindings the test shadows or nothing uses, which
+/// [`lint_hooks`] reports instead, once for all of the tests. The other statements are left as they
+/// are, so that rustc still reports e.g. a binding which only the hooks of another test use.
+pub fn allow_unused_bindings(hooks_data: &mut HooksData, test: &ItemFn) {
+    let test_bindings = let_bindings(&test.block.stmts);
+    let used: Vec<Ident> = uses(&test.block.stmts)
+        .into_iter()
+        .chain(
+            [
+                &hooks_data.before_each,
+                &hooks_data.invariants,
+                &hooks_data.after_each,
+            ]
+            .into_iter()
+            .flatten()
+            .flat_map(|hook| uses(&hook.stmts)),
+        )
+        .collect();
+
+    let hooks = hooks_data
+        .before_each
+        .iter_mut()
+        .chain(hooks_data.invariants.iter_mut());
+    for stmt in hooks.flat_map(|hook| &mut hook.stmts) {
+        let Stmt::Local(local) = stmt else {
+            continue;
+        };
+        let reported = pat_idents(&local.pat).iter().any(|binding| {
+            !binding.to_string().starts_with('_')
+                && (test_bindings.contains(binding) || !used.contains(binding))
+        });
+        if reported {
+            local.attrs.push(parse_quote!(#[allow(unused_variables)]));
+        }
+    }
+}
+
+/// Warns about the code of the `before_each`, `invariant` and `after_each` hooks that doesn't do
+/// what it seems to once the hooks are inserted into the `test` named `name`:
+/// - the bindings of the `before_each` and `invariant` hooks which the test shadows,
+/// - the uses in `after_each` of the bindings the test shadows or moves,
+/// - the bindings of the `before_each` and `invariant` hooks which nothing uses.
+pub fn lint_hooks(
+    hooks_data: &HooksData,
+    test: &ItemFn,
+    test_allowed: &ShadowAllowed,
+    name: &str,
+    diagnostics: &mut HookDiagnostics,
+) {
+    if test_allowed.all {
+        return;
+    }
+
+    // the hooks declaring the bindings the test can use, with their bindings
+    let providers: Vec<(&str, &Hook, Vec<Ident>)> = hooks_data
+        .before_each
+        .iter()
+        .map(|hook| ("before_each", hook))
+        .chain(hooks_data.invariants.iter().map(|hook| ("invariant", hook)))
+        .filter(|(_, hook)| !hook.allowed.all)
+        .map(|(kind, hook)| {
+            let bindings = let_bindings(&hook.stmts)
+                .into_iter()
+                .filter(|binding| !hook.allowed.allows(&binding.to_string()))
+                .collect();
+            (kind, hook, bindings)
+        })
+        .collect();
+    let provider_of = |binding: &str| {
+        providers
+            .iter()
+            .find(|(_, _, bindings)| bindings.iter().any(|provided| provided == binding))
+    };
+
+    let test_bindings: Vec<Ident> = let_bindings(&test.block.stmts)
+        .into_iter()
+        .filter(|binding| !test_allowed.allows(&binding.to_string()))
+        .collect();
+    for binding in &test_bindings {
+        if let Some((kind, hook, _)) = provider_of(&binding.to_string()) {
+            diagnostics.warn(
+                binding.span(),
+                kind,
+                &hook.name,
+                &format!("`{}` shadows the binding declared in the hook", binding),
+                name,
+            );
+        }
+    }
+
+    let mut moves = MoveFinder::default();
+    for stmt in &test.block.stmts {
+        moves.visit_stmt(stmt);
+    }
+    let params: Vec<Ident> = test
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) => Some(pat_idents(&pat_type.pat)),
+            FnArg::Receiver(_) => None,
+        })
+        .flatten()
+        .collect();
+    for hook in &hooks_data.after_each {
+        if hook.allowed.all {
+            continue;
+        }
+
+        let mut warned = vec![];
+        for used in uses(&hook.stmts) {
+            let binding = used.to_string();
+            if warned.contains(&binding) {
+                continue;
+            }
+
+            let shadowing_provider = provider_of(&binding)
+                .filter(|_| test_bindings.iter().any(|shadowing| *shadowing == binding));
+            let message = if let Some((kind, provider, _)) = shadowing_provider {
+                format!(
+                    "`{}` is the binding declared by the test, which shadows the one declared in {} `{}`",
+                    binding, kind, provider.name
+                )
+            } else if moves.moved.contains(&binding)
+                && (provider_of(&binding).is_some()
+                    || test_bindings.iter().any(|declared| *declared == binding)
+                    || params.iter().any(|param| *param == binding))
+            {
+                format!(
+                    "`{}` is moved by the test before the hook uses it, unless it is `Copy`",
+                    binding
+                )
+            } else {
+                continue;
+            };
+            diagnostics.warn(used.span(), "after_each", &hook.name, &message, name);
+            warned.push(binding);
+        }
+    }
+
+    let used: Vec<String> = uses(&test.block.stmts)
+        .into_iter()
+        .chain(
+            [
+                &hooks_data.before_each,
+                &hooks_data.invariants,
+                &hooks_data.after_each,
+            ]
+            .into_iter()
+            .flatten()
+            .flat_map(|hook| uses(&hook.stmts)),
+        )
+        .map(|used| used.to_string())
+        .collect();
+    for (kind, hook, bindings) in &providers {
+        for binding in bindings {
+            if !used.contains(&binding.to_string()) {
+                diagnostics.warn(
+                    binding.span(),
+                    kind,
+                    &hook.name,
+                    &format!("`{}` is not used by the test or by its hooks", binding),
+                    name,
+                );
+            }
+        }
+    }
+}
+
+/// Returns the bindings of the `let` statements, except the ones starting with an underscore.
+pub fn let_bindings(stmts: &[Stmt]) -> Vec<Ident> {
+    stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Local(local) => Some(pat_idents(&local.pat)),
+            _ => None,
+        })
+        .flatten()
+        .filter(|binding| !binding.to_string().starts_with('_'))
+        .collect()
+}
+
+/// Returns the identifiers bound by the pattern.
+pub fn pat_idents(pat: &Pat) -> Vec<Ident> {
+    match pat {
+        Pat::Ident(pat_ident) => {
+            let mut idents = vec![pat_ident.ident.clone()];
+            if let Some((_, subpat)) = &pat_ident.subpat {
+                idents.extend(pat_idents(subpat));
+            }
+            idents
+        }
+        Pat::Tuple(tuple) => tuple.elems.iter().flat_map(pat_idents).collect(),
+        Pat::TupleStruct(tuple_struct) => tuple_struct.elems.iter().flat_map(pat_idents).collect(),
+        Pat::Struct(pat_struct) => pat_struct
+            .fields
+            .iter()
+            .flat_map(|field| pat_idents(&field.pat))
+            .collect(),
+        Pat::Slice(slice) => slice.elems.iter().flat_map(pat_idents).collect(),
+        Pat::Reference(reference) => pat_idents(&reference.pat),
+        Pat::Type(pat_type) => pat_idents(&pat_type.pat),
+        Pat::Paren(paren) => pat_idents(&paren.pat),
+        Pat::Or(or) => or.cases.first().map(pat_idents).unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+/// Returns the identifiers the statements may use as variables, i.e. both of the ones returned by
+/// [`split_uses`].
+pub fn uses(stmts: &[Stmt]) -> Vec<Ident> {
+    let (mut uses, guesses) = split_uses(stmts);
+    uses.extend(guesses);
+
+    uses
+}
+
+/// Returns the identifiers the statements use as variables, i.e. the paths in the code, including
+/// the arguments of the macros taking expressions, e.g. `assert_eq!(a, b)`, and separately the
+/// identifiers in the arguments of the other macros, which may be variables or not.
+pub fn split_uses(stmts: &[Stmt]) -> (Vec<Ident>, Vec<Ident>) {
+    let mut finder = UseFinder::default();
+    for stmt in stmts {
+        finder.visit_stmt(stmt);
+    }
+
+    (finder.uses, finder.guesses)
+}
+
+#[derive(Default)]
+struct UseFinder {
+    uses: Vec<Ident>,
+    guesses: Vec<Ident>,
+}
+
+impl UseFinder {
+    fn visit_tokens(&mut self, tokens: TokenStream) {
+        let mut after_dot = false;
+        for token in tokens {
+            match &token {
+                // fields and methods, e.g. `cfg.client`
+                TokenTree::Ident(_) if after_dot => {}
+                TokenTree::Ident(ident) => self.guesses.push(ident.clone()),
+                TokenTree::Group(group) => self.visit_tokens(group.stream()),
+                TokenTree::Punct(_) | TokenTree::Literal(_) => {}
+            }
+            after_dot = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '.');
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UseFinder {
+    fn visit_expr_path(&mut self, path: &'ast ExprPath) {
+        if let (None, Some(ident)) = (&path.qself, path.path.get_ident()) {
+            self.uses.push(ident.clone());
+        }
+        visit::visit_expr_path(self, path);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        match mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            Ok(args) => args.iter().for_each(|arg| self.visit_expr(arg)),
+            Err(_) => self.visit_tokens(mac.tokens.clone()),
+        }
+    }
+
+    fn visit_item(&mut self, _item: &'ast Item) {}
+}
+
+/// Finds the variables the statements move, i.e. the ones passed by value to functions and
+/// methods, assigned, or returned.
+#[derive(Default)]
+struct MoveFinder {
+    moved: Vec<String>,
+}
+
+impl MoveFinder {
+    fn moves(&mut self, expr: &Expr) {
+        if let Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) = expr
+        {
+            if let Some(ident) = path.get_ident() {
+                self.moved.push(ident.to_string());
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for MoveFinder {
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        call.args.iter().for_each(|arg| self.moves(arg));
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        call.args.iter().for_each(|arg| self.moves(arg));
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_local(&mut self, local: &'ast Local) {
+        if let Some(init) = &local.init {
+            self.moves(&init.expr);
+        }
+        visit::visit_local(self, local);
+    }
+
+    fn visit_expr_assign(&mut self, assign: &'ast ExprAssign) {
+        self.moves(&assign.right);
+        visit::visit_expr_assign(self, assign);
+    }
+
+    fn visit_expr_return(&mut self, ret: &'ast ExprReturn) {
+        if let Some(expr) = &ret.expr {
+            self.moves(expr);
+        }
+        visit::visit_expr_return(self, ret);
+    }
+
+    fn visit_item(&mut self, _item: &'ast Item) {}
+}
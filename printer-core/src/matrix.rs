@@ -0,0 +1,52 @@
+use quote::format_ident;
+use syn::{parse_quote, Expr, Ident, Stmt};
+
+use crate::utils::name_suffix;
+
+/// Returns every combination of the values of the matrix variables, as the name of the module
+/// holding the tests of that combination and the `let` statements binding its values, e.g.
+/// `caller_alice_ctor_new_false` and `let caller = alice; let ctor = new_false;`.
+pub fn matrix_combinations(matrix: &[(Ident, Vec<Expr>)]) -> Vec<(Ident, Vec<Stmt>)> {
+    let mut combinations: Vec<Vec<(&Ident, &Expr)>> = vec![vec![]];
+    for (name, values) in matrix {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.push((name, value));
+                    combination
+                })
+            })
+            .collect();
+    }
+
+    let mut mod_names: Vec<String> = vec![];
+    combinations
+        .into_iter()
+        .enumerate()
+        .map(|(idx, combination)| {
+            let mut mod_name = combination
+                .iter()
+                .map(|(name, value)| format!("{}_{}", name, name_suffix(&[(*value).clone()])))
+                .collect::<Vec<_>>()
+                .join("_");
+            if mod_names.contains(&mod_name) {
+                mod_name = format!("{}_{}", mod_name, idx + 1);
+            }
+            mod_names.push(mod_name.clone());
+
+            let bindings = combination
+                .iter()
+                .map(|(name, value)| -> Stmt {
+                    parse_quote! {
+                        #[allow(unused_variables)]
+                        let #name = #value;
+                    }
+                })
+                .collect();
+
+            (format_ident!("{}", mod_name), bindings)
+        })
+        .collect()
+}
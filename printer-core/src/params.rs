@@ -0,0 +1,149 @@
+use proc_macro_error::abort;
+use syn::{Attribute, FnArg, Ident, ItemFn, Meta, Stmt};
+
+use crate::diagnostics::HookDiagnostics;
+use crate::lint::{let_bindings, pat_idents, split_uses};
+use crate::utils::{count_test_cases, HooksData, PrinterAttributeVariant};
+
+/// Returns true if the hook is marked with `only`, e.g. `#[before_each(only)]`.
+pub fn is_only_hook(attrs: &[Attribute], variant: &PrinterAttributeVariant) -> bool {
+    let Some(attr) = attrs.iter().find(|attr| match &attr.meta {
+        Meta::List(list) => list.path.segments[0].ident == hook_attr_name(variant),
+        _ => false,
+    }) else {
+        return false;
+    };
+
+    if *variant == PrinterAttributeVariant::BeforeAll {
+        abort!(attr, "`before_all` doesn't accept arguments");
+    }
+    match attr.parse_args::<Ident>() {
+        Ok(arg) if arg == "only" => true,
+        _ => abort!(
+            attr,
+            "`{}` accepts only `only`, e.g. `#[{}(only)]`",
+            hook_attr_name(variant),
+            hook_attr_name(variant)
+        ),
+    }
+}
+
+fn hook_attr_name(variant: &PrinterAttributeVariant) -> &'static str {
+    match variant {
+        PrinterAttributeVariant::BeforeEach => "before_each",
+        PrinterAttributeVariant::AfterEach => "after_each",
+        PrinterAttributeVariant::Invariant => "invariant",
+        PrinterAttributeVariant::BeforeAll => "before_all",
+    }
+}
+
+/// Returns the names of the parameters of the tests in `items`.
+pub fn test_params(items: &[syn::Item]) -> Vec<String> {
+    let mut params: Vec<String> = vec![];
+    for item in items {
+        if let syn::Item::Fn(func) = item {
+            if count_test_cases(func).is_some() {
+                params.extend(fn_params(func).iter().map(Ident::to_string));
+            }
+        }
+    }
+    params.sort();
+    params.dedup();
+
+    params
+}
+
+/// Returns the hooks to insert into the `test` named `name`. A hook using one of the `params` of
+/// the tests of the module, which the test neither has nor gets from `bindings`, is left out if it
+/// is marked with `only`, and reported as an error otherwise.
+pub fn hooks_for_test(
+    hooks_data: &HooksData,
+    test: &ItemFn,
+    bindings: &[Stmt],
+    params: &[String],
+    name: &str,
+    diagnostics: &mut HookDiagnostics,
+) -> HooksData {
+    let provided: Vec<String> = fn_params(test)
+        .into_iter()
+        .chain(bindings.iter().flat_map(|stmt| match stmt {
+            Stmt::Local(local) => pat_idents(&local.pat),
+            _ => vec![],
+        }))
+        .map(|ident| ident.to_string())
+        .collect();
+    // the bindings of the hooks shadow the parameters
+    let hook_bindings: Vec<String> = [
+        &hooks_data.before_each,
+        &hooks_data.invariants,
+        &hooks_data.after_each,
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|hook| let_bindings(&hook.stmts))
+    .map(|ident| ident.to_string())
+    .collect();
+    let test_bindings: Vec<String> = let_bindings(&test.block.stmts)
+        .iter()
+        .map(Ident::to_string)
+        .collect();
+
+    let mut hooks_data = hooks_data.clone();
+    let hooks = [
+        ("before_each", &mut hooks_data.before_each),
+        ("invariant", &mut hooks_data.invariants),
+        ("after_each", &mut hooks_data.after_each),
+    ];
+    for (kind, hooks) in hooks {
+        hooks.retain(|hook| {
+            let is_missing = |param: &String| {
+                params.contains(param)
+                    && !provided.contains(param)
+                    && !hook_bindings.contains(param)
+                    // `after_each` runs after the code of the test
+                    && (kind != "after_each" || !test_bindings.contains(param))
+            };
+            let (uses, guesses) = split_uses(&hook.stmts);
+            let mut missing: Vec<Ident> = vec![];
+            for used in uses {
+                let param = used.to_string();
+                if is_missing(&param) && !missing.iter().any(|missing| *missing == param) {
+                    missing.push(used);
+                }
+            }
+            if !missing.is_empty() && hook.only {
+                return false;
+            }
+
+            for param in missing {
+                diagnostics.missing_param(param.span(), kind, &hook.name, &param.to_string(), name);
+            }
+            // the identifiers in macros may be fields, methods or tokens of any kind
+            for guess in guesses {
+                let param = guess.to_string();
+                if is_missing(&param) {
+                    let message = format!(
+                        "the macro may use `{}`, which is a parameter of other tests only",
+                        param
+                    );
+                    diagnostics.warn(guess.span(), kind, &hook.name, &message, name);
+                }
+            }
+            true
+        });
+    }
+
+    hooks_data
+}
+
+fn fn_params(func: &ItemFn) -> Vec<Ident> {
+    func.sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) => Some(pat_idents(&pat_type.pat)),
+            FnArg::Receiver(_) => None,
+        })
+        .flatten()
+        .collect()
+}
@@ -0,0 +1,76 @@
+use proc_macro_error::abort;
+use syn::{parse2, parse_quote, Attribute, ForeignItemFn, ItemFn, LitStr};
+
+use crate::utils::{count_test_cases, take_attr};
+
+/// Turns a test declared with `#[todo]` and without a body, e.g. `#[todo] fn handles_reentrancy();`,
+/// into an empty test. Other items are returned as they are.
+pub fn declare_todo(item: syn::Item) -> syn::Item {
+    let syn::Item::Verbatim(tokens) = item else {
+        return item;
+    };
+
+    match parse2::<ForeignItemFn>(tokens.clone()) {
+        Ok(declaration)
+            if declaration
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("todo")) =>
+        {
+            let attrs = declaration
+                .attrs
+                .into_iter()
+                .filter(|attr| attr.path().is_ident("todo") || attr.path().is_ident("doc"));
+            let name = declaration.sig.ident;
+
+            syn::Item::Fn(parse_quote! {
+                #(#attrs)*
+                #[test]
+                fn #name() {}
+            })
+        }
+        _ => syn::Item::Verbatim(tokens),
+    }
+}
+
+/// Removes `#[skip(...)]` or `#[todo]` from the test and returns the reason it should be ignored
+/// with. Tests marked with `#[todo]` don't need to be marked as tests.
+pub fn take_pending_reason(func: &mut ItemFn) -> Option<String> {
+    if let Some(skip_attr) = take_attr(&mut func.attrs, "skip") {
+        if count_test_cases(func).is_none() {
+            abort!(skip_attr, "`skip` should be used on test functions");
+        }
+
+        return Some(attr_reason(&skip_attr).unwrap_or_else(|| "skipped".to_string()));
+    }
+
+    if let Some(todo_attr) = take_attr(&mut func.attrs, "todo") {
+        if count_test_cases(func).is_none() {
+            func.attrs.push(parse_quote! { #[test] });
+        }
+
+        return Some(match attr_reason(&todo_attr) {
+            Some(reason) => format!("todo: {}", reason),
+            None => "todo".to_string(),
+        });
+    }
+
+    None
+}
+
+/// Returns true if the function is marked with `#[skip(...)]` or `#[todo]`.
+pub fn is_pending(func: &ItemFn) -> bool {
+    func.attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("skip") || attr.path().is_ident("todo"))
+}
+
+fn attr_reason(attr: &Attribute) -> Option<String> {
+    match &attr.meta {
+        syn::Meta::Path(_) => None,
+        _ => match attr.parse_args::<LitStr>() {
+            Ok(reason) => Some(reason.value()),
+            Err(e) => abort!(e.span(), "the reason should be a string literal: {}", e),
+        },
+    }
+}
@@ -0,0 +1,209 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse2, parse_quote, Attribute, Block, Ident, ItemFn, ItemMacro, ReturnType, Stmt, Token,
+};
+
+use crate::args::PropertyHooks;
+use crate::utils::HooksData;
+use crate::{insert_code_front, insert_hooks, insert_ignore, insert_syncing_code, syncing_code};
+
+/// Property testing macros printer knows how to insert the hooks into.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PropertyMacroVariant {
+    Proptest,
+    Quickcheck,
+}
+
+impl PropertyMacroVariant {
+    pub fn try_from_macro(item_macro: &ItemMacro) -> Option<Self> {
+        let last = item_macro.mac.path.segments.last()?.ident.to_string();
+        if last == "proptest" {
+            return Some(Self::Proptest);
+        } else if last == "quickcheck" {
+            return Some(Self::Quickcheck);
+        }
+
+        None
+    }
+}
+
+/// Contents of a `proptest! { ... }` or `quickcheck! { ... }` invocation.
+struct PropertyMacro {
+    inner_attrs: Vec<Attribute>,
+    properties: Vec<Property>,
+}
+
+/// A single property declared inside of a property testing macro. Its arguments are not valid Rust
+/// (e.g. `x in 0..100u32`), so they are kept as they are and the rest is parsed as a function
+/// without arguments, so that the hooks can be inserted the same way as for regular tests.
+struct Property {
+    args: TokenStream,
+    func: ItemFn,
+}
+
+impl Parse for PropertyMacro {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner_attrs = input.call(Attribute::parse_inner)?;
+        let mut properties = vec![];
+        while !input.is_empty() {
+            properties.push(input.parse()?);
+        }
+
+        Ok(Self {
+            inner_attrs,
+            properties,
+        })
+    }
+}
+
+impl Parse for Property {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        input.parse::<Token![fn]>()?;
+        let name: Ident = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let args: TokenStream = content.parse()?;
+        let output: ReturnType = input.parse()?;
+        let block: Block = input.parse()?;
+
+        let mut func: ItemFn = parse_quote! {
+            fn #name() #output #block
+        };
+        func.attrs = attrs;
+
+        Ok(Self { args, func })
+    }
+}
+
+impl Property {
+    fn to_tokens(&self) -> TokenStream {
+        let Property { args, func } = self;
+        let attrs = &func.attrs;
+        let name = &func.sig.ident;
+        let output = &func.sig.output;
+        let block = &func.block;
+
+        quote! {
+            #(#attrs)* fn #name(#args) #output #block
+        }
+    }
+}
+
+/// Inserts the hooks into every property of the `proptest!` or `quickcheck!` invocation. Returns
+/// the items that should replace the invocation and the number of test cases they contain.
+pub fn expand_property_macro(
+    item_macro: &ItemMacro,
+    variant: PropertyMacroVariant,
+    hooks_data: &HooksData,
+    property_hooks: PropertyHooks,
+    bindings: &[Stmt],
+    focused: bool,
+) -> (Vec<syn::Item>, usize) {
+    let mut property_macro = match parse2::<PropertyMacro>(item_macro.mac.tokens.clone()) {
+        Ok(property_macro) => property_macro,
+        Err(e) => abort!(e.span(), "printer could not parse the property test: {}", e),
+    };
+    // properties can't be focused with `#[only]`, so they are ignored if any other test is, and
+    // they don't wait for `before_all`, which doesn't count them
+    let test_cases_num = if focused {
+        for property in property_macro.properties.iter_mut() {
+            insert_ignore(&mut property.func, "not focused with #[only]");
+        }
+        0
+    } else {
+        property_macro.properties.len()
+    };
+
+    let items = match property_hooks {
+        PropertyHooks::PerCase => {
+            for property in property_macro.properties.iter_mut() {
+                insert_hooks(&mut property.func, hooks_data);
+                insert_code_front(&mut property.func, &[bindings.to_vec()]);
+                if !hooks_data.before_all.is_empty() && !focused {
+                    insert_syncing_code_once(&mut property.func);
+                }
+            }
+
+            let path = &item_macro.mac.path;
+            let attrs = &item_macro.attrs;
+            let inner_attrs = &property_macro.inner_attrs;
+            let properties = property_macro.properties.iter().map(Property::to_tokens);
+            vec![parse_quote! {
+                #(#attrs)*
+                #path! {
+                    #(#inner_attrs)*
+                    #(#properties)*
+                }
+            }]
+        }
+        PropertyHooks::PerProperty => {
+            if variant == PropertyMacroVariant::Quickcheck {
+                abort!(
+                    item_macro.mac.path,
+                    "quickcheck! properties can't capture variables from the hooks, use `property_hooks = per_case` instead"
+                );
+            }
+
+            let config = property_macro.inner_attrs.iter().map(|attr| {
+                match attr.parse_args::<syn::Expr>() {
+                    Ok(config) => quote! { #config, },
+                    Err(e) => abort!(
+                        e.span(),
+                        "printer could not parse the proptest config: {}",
+                        e
+                    ),
+                }
+            });
+            let config: Vec<TokenStream> = config.collect();
+
+            property_macro
+                .properties
+                .into_iter()
+                .map(|Property { args, func }| {
+                    let path = &item_macro.mac.path;
+                    let attrs = &func.attrs;
+                    let name = &func.sig.ident;
+                    let body = &func.block;
+                    let mut test: ItemFn = parse_quote! {
+                        #(#attrs)*
+                        fn #name() {
+                            #path!(#(#config)* |(#args)| #body);
+                        }
+                    };
+
+                    insert_hooks(&mut test, hooks_data);
+                    insert_code_front(&mut test, &[bindings.to_vec()]);
+                    if !hooks_data.before_all.is_empty() && !focused {
+                        insert_syncing_code(&mut test);
+                    }
+
+                    test.into()
+                })
+                .collect()
+        }
+    };
+
+    (items, test_cases_num)
+}
+
+/// Property bodies run once per generated case, but the test is allowed to run only once
+/// `before_all` sends a single message for it, so the waiting happens only in the first case.
+fn insert_syncing_code_once(function: &mut ItemFn) {
+    let syncing_code = syncing_code();
+    function.block.stmts.insert(
+        0,
+        parse_quote! {
+            {
+                static PRINTER_SYNC_ONCE: std::sync::Once = std::sync::Once::new();
+                PRINTER_SYNC_ONCE.call_once(|| {
+                    #syncing_code
+                });
+            }
+        },
+    );
+}
@@ -0,0 +1,80 @@
+use proc_macro_error::abort;
+use syn::{parse_quote, Expr, ItemFn, Lit, LitInt, Stmt};
+
+use crate::utils::{count_test_cases, take_attr};
+
+/// Parses the number of retries of the `retries` macro argument, i.e. `retries = 3`.
+pub fn parse_retries_arg(value: &Expr) -> usize {
+    match value {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => parse_retries(lit),
+        _ => abort!(value, "`retries` should be a number, e.g. `retries = 3`"),
+    }
+}
+
+/// Removes `#[retries(n)]` from the test and returns the number of retries.
+pub fn take_retries(func: &mut ItemFn) -> Option<usize> {
+    let retries_attr = take_attr(&mut func.attrs, "retries")?;
+    if count_test_cases(func).is_none() {
+        abort!(retries_attr, "`retries` should be used on test functions");
+    }
+
+    match retries_attr.parse_args::<LitInt>() {
+        Ok(lit) => Some(parse_retries(&lit)),
+        Err(e) => abort!(
+            e.span(),
+            "`retries` should contain a number, e.g. `#[retries(3)]`: {}",
+            e
+        ),
+    }
+}
+
+fn parse_retries(lit: &LitInt) -> usize {
+    match lit.base10_parse::<usize>() {
+        Ok(retries) => retries,
+        Err(e) => abort!(lit, "invalid number of retries: {}", e),
+    }
+}
+
+/// Wraps the whole body of the test, including the hooks, in a loop running it until it passes or
+/// it fails `retries + 1` times. Every attempt is run with `catch_unwind`, so that panics fail only
+/// the attempt.
+pub fn insert_retries(function: &mut ItemFn, retries: usize) {
+    let name = function.sig.ident.to_string();
+    let stmts = std::mem::take(&mut function.block.stmts);
+    let return_type: syn::Type = match &function.sig.output {
+        syn::ReturnType::Default => parse_quote!(()),
+        syn::ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+
+    let attempt: Stmt = if function.sig.asyncness.is_some() {
+        parse_quote! {
+            let printer_attempt: std::thread::Result<#return_type> =
+                printer::runtime::catch_unwind(async { #(#stmts)* }).await;
+        }
+    } else {
+        let output = &function.sig.output;
+        parse_quote! {
+            let printer_attempt: std::thread::Result<#return_type> =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| #output { #(#stmts)* }));
+        }
+    };
+
+    function.block.stmts = vec![
+        parse_quote! {
+            let mut printer_attempts = printer::runtime::Attempts::new(#name, #retries);
+        },
+        Stmt::Expr(
+            parse_quote! {
+                loop {
+                    #attempt
+                    if let Some(printer_result) = printer_attempts.check(printer_attempt) {
+                        break printer_result;
+                    }
+                }
+            },
+            None,
+        ),
+    ];
+}
@@ -0,0 +1,236 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use syn::{parse2, parse_quote, punctuated::Punctuated, Ident, ItemFn, ItemStatic, Token};
+
+use crate::utils::count_test_cases;
+
+/// A test taking part in the completions of the module.
+struct CompletionTest {
+    name: String,
+    /// Number of cases of the test, `0` for the tests that are not run at all.
+    cases_num: usize,
+    ignored: bool,
+    /// Indices of the tests it waits for, with the `depends_on` ident they come from. Dependencies
+    /// without the ident come from `sequential` and are not required to run.
+    dependencies: Vec<(usize, Option<Ident>)>,
+}
+
+/// Removes all of the `#[depends_on(...)]` attributes from the test and returns the names of the
+/// tests it depends on.
+pub fn take_depends_on(func: &mut ItemFn) -> Vec<Ident> {
+    let mut depends_on = vec![];
+    let is_test = count_test_cases(func).is_some();
+    func.attrs.retain(|attr| {
+        if !attr.path().is_ident("depends_on") {
+            return true;
+        }
+        if !is_test {
+            abort!(attr, "`depends_on` should be used on test functions");
+        }
+
+        match attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated) {
+            Ok(names) if !names.is_empty() => depends_on.extend(names),
+            Ok(_) => abort!(attr, "`depends_on` should list at least one test"),
+            Err(e) => abort!(
+                e.span(),
+                "`depends_on` should contain a list of tests, e.g. `#[depends_on(deploy_works)]`: {}",
+                e
+            ),
+        }
+        false
+    });
+
+    depends_on
+}
+
+/// Makes the tests wait for their `depends_on` and, with `sequential`, for the test declared before
+/// them. Returns the static holding their completions, if any test waits, with the positions of the
+/// tests in `items` taking part in them.
+pub fn insert_completions(
+    items: &mut [syn::Item],
+    running_tests: &[(usize, usize)],
+    declared: &[(String, Vec<Ident>)],
+    not_running: &[String],
+    sequential: bool,
+) -> Option<(ItemStatic, Vec<usize>)> {
+    let names: Vec<String> = running_tests
+        .iter()
+        .map(|&(idx, _)| match &items[idx] {
+            syn::Item::Fn(func) => func.sig.ident.to_string(),
+            _ => unreachable!("running tests are functions"),
+        })
+        .collect();
+    let has_dependencies = declared
+        .iter()
+        .any(|(_, depends_on)| !depends_on.is_empty());
+    if running_tests.is_empty() || !(sequential || has_dependencies) {
+        return None;
+    }
+
+    // resolving the names to the positions in `running_tests`, or in `not_running` (shifted by the
+    // number of the running tests)
+    let mut dependencies: Vec<Vec<(usize, Option<Ident>)>> = vec![vec![]; running_tests.len()];
+    for (test, (_, depends_on)) in declared.iter().enumerate() {
+        if sequential && test > 0 {
+            dependencies[test].push((test - 1, None));
+        }
+        for dependency in depends_on {
+            let matching: Vec<usize> = (0..running_tests.len())
+                .filter(|&other| *dependency == declared[other].0 || *dependency == names[other])
+                .collect();
+            if !matching.is_empty() {
+                dependencies[test].extend(
+                    matching
+                        .into_iter()
+                        .map(|other| (other, Some(dependency.clone()))),
+                );
+            } else if let Some(other) = not_running.iter().position(|name| dependency == name) {
+                dependencies[test].push((running_tests.len() + other, Some(dependency.clone())));
+            } else {
+                abort!(
+                    dependency,
+                    "there is no test `{}` in this module",
+                    dependency
+                );
+            }
+        }
+    }
+    check_cycles(&names, &dependencies);
+
+    // only the tests waiting for other tests and the tests they wait for take part
+    let mut participants: Vec<bool> = (0..running_tests.len() + not_running.len())
+        .map(|test| sequential || dependencies.get(test).is_some_and(|deps| !deps.is_empty()))
+        .collect();
+    for (dependency, _) in dependencies.iter().flatten() {
+        participants[*dependency] = true;
+    }
+    let completion_idx: Vec<usize> = participants
+        .iter()
+        .scan(0, |next, &participant| {
+            let idx = *next;
+            *next += participant as usize;
+            Some(idx)
+        })
+        .collect();
+
+    let mut completion_tests = vec![];
+    let mut positions = vec![];
+    for (test, &(idx, cases_num)) in running_tests.iter().enumerate() {
+        if !participants[test] {
+            continue;
+        }
+        positions.push(idx);
+        let syn::Item::Fn(func) = &mut items[idx] else {
+            unreachable!("running tests are functions");
+        };
+
+        completion_tests.push(CompletionTest {
+            name: names[test].clone(),
+            cases_num,
+            ignored: func.attrs.iter().any(|attr| attr.path().is_ident("ignore")),
+            dependencies: dependencies[test]
+                .iter()
+                .map(|(dependency, ident)| (completion_idx[*dependency], ident.clone()))
+                .collect(),
+        });
+        insert_completion(func, completion_idx[test]);
+    }
+    for (other, name) in not_running.iter().enumerate() {
+        if participants[running_tests.len() + other] {
+            completion_tests.push(CompletionTest {
+                name: name.clone(),
+                cases_num: 0,
+                ignored: true,
+                dependencies: vec![],
+            });
+        }
+    }
+
+    Some((
+        parse2(completions_static(&completion_tests)).unwrap(),
+        positions,
+    ))
+}
+
+/// Aborts if the tests depend on each other in a cycle.
+fn check_cycles(names: &[String], dependencies: &[Vec<(usize, Option<Ident>)>]) {
+    /// `path` holds the tests visited so far with the edges leading to the next one.
+    fn visit<'a>(
+        test: usize,
+        dependencies: &'a [Vec<(usize, Option<Ident>)>],
+        path: &mut Vec<(usize, &'a Option<Ident>)>,
+        visited: &mut [bool],
+        names: &[String],
+    ) {
+        if let Some(start) = path.iter().position(|(other, _)| *other == test) {
+            let cycle = &path[start..];
+            let description: Vec<String> = cycle
+                .iter()
+                .map(|(other, _)| *other)
+                .chain(std::iter::once(test))
+                .map(|other| format!("`{}`", names[other]))
+                .collect();
+            // `sequential` dependencies point backwards only, the cycle contains a `depends_on`
+            let ident = cycle.iter().find_map(|(_, ident)| ident.as_ref()).unwrap();
+            abort!(ident, "dependency cycle: {}", description.join(" -> "));
+        }
+        // the tests that are not run don't have any dependencies
+        if visited.get(test).copied().unwrap_or(true) {
+            return;
+        }
+        visited[test] = true;
+
+        for (dependency, ident) in &dependencies[test] {
+            path.push((test, ident));
+            visit(*dependency, dependencies, path, visited, names);
+            path.pop();
+        }
+    }
+
+    let mut visited = vec![false; dependencies.len()];
+    for test in 0..dependencies.len() {
+        visit(test, dependencies, &mut vec![], &mut visited, names);
+    }
+}
+
+/// Returns the static holding the completions of the tests of the module.
+fn completions_static(tests: &[CompletionTest]) -> TokenStream {
+    let tests = tests.iter().map(|test| {
+        let CompletionTest {
+            name,
+            cases_num,
+            ignored,
+            ..
+        } = test;
+        let dependencies = test.dependencies.iter().map(|(dependency, ident)| {
+            let required = ident.is_some();
+            quote::quote!((#dependency, #required))
+        });
+
+        quote::quote! {
+            (#name, #cases_num, #ignored, &[#(#dependencies),*])
+        }
+    });
+
+    quote::quote! {
+        static PRINTER_COMPLETIONS: printer::runtime::Completions = printer::runtime::Completions::new(
+            module_path!(),
+            &[#(#tests),*],
+        );
+    }
+}
+
+/// Makes the test at the `idx` of the completions wait for its dependencies and skip itself when
+/// any of them failed. The outcome of the tests returning a value is checked by
+/// [`insert_test_guard`](crate::suite::insert_test_guard).
+fn insert_completion(function: &mut ItemFn, idx: usize) {
+    function.block.stmts.insert(
+        0,
+        parse_quote! {
+            let _printer_completion = match PRINTER_COMPLETIONS.wait(#idx) {
+                Ok(printer_completion) => printer_completion,
+                Err(printer_reason) => printer::skip!("{}", printer_reason),
+            };
+        },
+    );
+}
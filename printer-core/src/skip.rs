@@ -0,0 +1,86 @@
+use syn::fold::{self, Fold};
+use syn::{parse_quote, Block, Expr, ExprAsync, ExprClosure, Item, Macro, Stmt};
+
+/// Runs `end_hooks` before every `skip!(...)` in `stmts` returns from the test, returns true if any
+/// was found. Returning from closures, async blocks and nested items doesn't end the test.
+pub fn rewrite_skips(stmts: &mut Vec<Stmt>, end_hooks: &[Stmt]) -> bool {
+    let mut rewriter = SkipRewriter {
+        end_hooks,
+        found: false,
+    };
+    *stmts = std::mem::take(stmts)
+        .into_iter()
+        .map(|stmt| rewriter.fold_stmt(stmt))
+        .collect();
+
+    rewriter.found
+}
+
+struct SkipRewriter<'a> {
+    end_hooks: &'a [Stmt],
+    found: bool,
+}
+
+impl SkipRewriter<'_> {
+    fn rewrite(&mut self, mac: Macro) -> Block {
+        self.found = true;
+        let end_hooks = self.end_hooks;
+
+        parse_quote! {
+            {
+                #(#end_hooks)*
+                #mac
+            }
+        }
+    }
+}
+
+impl Fold for SkipRewriter<'_> {
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        match stmt {
+            Stmt::Macro(stmt_macro) if is_skip(&stmt_macro.mac) => Stmt::Expr(
+                Expr::Block(syn::ExprBlock {
+                    attrs: stmt_macro.attrs,
+                    label: None,
+                    block: self.rewrite(stmt_macro.mac),
+                }),
+                stmt_macro.semi_token,
+            ),
+            stmt => fold::fold_stmt(self, stmt),
+        }
+    }
+
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Macro(expr_macro) if is_skip(&expr_macro.mac) => Expr::Block(syn::ExprBlock {
+                attrs: expr_macro.attrs,
+                label: None,
+                block: self.rewrite(expr_macro.mac),
+            }),
+            expr => fold::fold_expr(self, expr),
+        }
+    }
+
+    fn fold_expr_closure(&mut self, closure: ExprClosure) -> ExprClosure {
+        closure
+    }
+
+    fn fold_expr_async(&mut self, async_block: ExprAsync) -> ExprAsync {
+        async_block
+    }
+
+    fn fold_item(&mut self, item: Item) -> Item {
+        item
+    }
+}
+
+fn is_skip(mac: &Macro) -> bool {
+    let segments: Vec<String> = mac
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+
+    segments == ["skip"] || segments == ["printer", "skip"]
+}
@@ -0,0 +1,39 @@
+use syn::{parse_quote, ItemFn, Stmt};
+
+use crate::insert_code_end;
+
+/// Marks the beginning and the end of the body of the test for the timer inserted with
+/// [`insert_slow_timer`]. It should be used before the hooks are inserted.
+pub fn insert_body_markers(function: &mut ItemFn) {
+    let body_started: Stmt = parse_quote! {
+        printer_slow.body_started();
+    };
+    let body_finished: Stmt = parse_quote! {
+        printer_slow.body_finished();
+    };
+
+    let return_type_is_union = matches!(function.sig.output, syn::ReturnType::Default);
+    insert_code_end(function, &[vec![body_finished]], return_type_is_union);
+    function.block.stmts.insert(0, body_started);
+}
+
+/// Times the test, including its hooks, and reports it once its body takes longer than
+/// `threshold_millis`. It should be used after the hooks are inserted.
+pub fn insert_slow_timer(function: &mut ItemFn, threshold_millis: u64) {
+    let name = function.sig.ident.to_string();
+    let finish: Stmt = parse_quote! {
+        printer_slow.finish();
+    };
+
+    let return_type_is_union = matches!(function.sig.output, syn::ReturnType::Default);
+    insert_code_end(function, &[vec![finish]], return_type_is_union);
+    function.block.stmts.insert(
+        0,
+        parse_quote! {
+            let mut printer_slow = printer::runtime::SlowTimer::start(
+                #name,
+                std::time::Duration::from_millis(#threshold_millis),
+            );
+        },
+    );
+}
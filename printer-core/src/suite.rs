@@ -0,0 +1,148 @@
+use proc_macro2::TokenStream;
+use syn::fold::{self, Fold};
+use syn::{
+    parse_quote, Expr, ExprAsync, ExprClosure, ExprReturn, Item, ItemFn, ReturnType, Stmt, Type,
+};
+
+/// Returns the static holding the suite of the module, which tracks the failures of its tests and
+/// prints the pending tests (or the `spec` report) once all of the other tests are finished.
+/// `tests` are the names of the running tests with their number of cases and whether they are
+/// ignored with `#[ignore]`.
+pub fn suite_static(
+    tests: &[(String, usize, bool)],
+    pending: &[(String, String)],
+    bail: bool,
+) -> TokenStream {
+    let names = tests.iter().map(|(test, ..)| test);
+    let cases_nums = tests.iter().map(|(_, cases_num, _)| cases_num);
+    let ignored = tests.iter().map(|(.., ignored)| ignored);
+    let pending_tests = pending.iter().map(|(test, _)| test);
+    let reasons = pending.iter().map(|(_, reason)| reason);
+
+    quote::quote! {
+        static PRINTER_SUITE: printer::runtime::Suite = printer::runtime::Suite::new(
+            module_path!(),
+            &[#((#names, #cases_nums, #ignored)),*],
+            &[#((#pending_tests, #reasons)),*],
+            #bail,
+        );
+    }
+}
+
+/// Makes the test enter the suite of the module, which greps it out or skips it in bail mode, and
+/// checks the value it returns when its failure has to be known, unless it returns `impl Trait`.
+pub fn insert_test_guard(function: &mut ItemFn, completion: bool, reporting: bool, bail: bool) {
+    let name = function.sig.ident.to_string();
+    let checked = (reporting || bail || completion)
+        && match &function.sig.output {
+            ReturnType::Default => false,
+            ReturnType::Type(_, return_type) => !matches!(**return_type, Type::ImplTrait(_)),
+        };
+    let mut stmts = std::mem::take(&mut function.block.stmts);
+    if checked {
+        let return_type = match &function.sig.output {
+            ReturnType::Type(_, return_type) => return_type,
+            ReturnType::Default => unreachable!("checked tests return a value"),
+        };
+        check_returns(&mut stmts, return_type);
+    }
+
+    let mut prologue: Vec<Stmt> = vec![if checked {
+        parse_quote! {
+            let printer_test = PRINTER_SUITE.enter_fallible(#name);
+        }
+    } else if reporting || bail {
+        parse_quote! {
+            let printer_test = PRINTER_SUITE.enter(#name);
+        }
+    } else {
+        parse_quote! {
+            let _printer_test = PRINTER_SUITE.enter(#name);
+        }
+    }];
+    if reporting {
+        prologue.push(parse_quote! {
+            if printer_test.is_grepped_out() {
+                return printer::runtime::Skipped::skipped();
+            }
+        });
+    }
+    // grepped out tests don't wait for their dependencies
+    if completion {
+        prologue.push(stmts.remove(0));
+    }
+    if reporting || bail {
+        prologue.push(parse_quote! {
+            if let Some(printer_reason) = printer_test.bail_reason() {
+                printer::skip!("{}", printer_reason);
+            }
+        });
+    }
+
+    function.block.stmts = prologue.into_iter().chain(stmts).collect();
+}
+
+/// Checks the value returned by the tail expression of the test and by its `return` expressions,
+/// like [`crate::skip::rewrite_skips`] does with `skip!`.
+fn check_returns(stmts: &mut Vec<Stmt>, return_type: &Type) {
+    let mut checker = ReturnChecker { return_type };
+    *stmts = std::mem::take(stmts)
+        .into_iter()
+        .map(|stmt| checker.fold_stmt(stmt))
+        .collect();
+
+    let tail = match stmts.pop() {
+        Some(Stmt::Expr(expr, None)) => expr,
+        Some(Stmt::Macro(stmt_macro)) if stmt_macro.semi_token.is_none() => {
+            Expr::Macro(syn::ExprMacro {
+                attrs: stmt_macro.attrs,
+                mac: stmt_macro.mac,
+            })
+        }
+        other => {
+            stmts.extend(other);
+            return;
+        }
+    };
+    stmts.push(Stmt::Expr(checker.checked(tail), None));
+}
+
+struct ReturnChecker<'a> {
+    return_type: &'a Type,
+}
+
+impl ReturnChecker<'_> {
+    fn checked(&self, expr: Expr) -> Expr {
+        let return_type = self.return_type;
+
+        parse_quote! {
+            {
+                let printer_result: #return_type = #expr;
+                printer_test.check(&printer_result);
+                printer_result
+            }
+        }
+    }
+}
+
+impl Fold for ReturnChecker<'_> {
+    fn fold_expr_return(&mut self, expr_return: ExprReturn) -> ExprReturn {
+        let expr_return = fold::fold_expr_return(self, expr_return);
+        ExprReturn {
+            expr: expr_return.expr.map(|expr| Box::new(self.checked(*expr))),
+            ..expr_return
+        }
+    }
+
+    fn fold_expr_closure(&mut self, closure: ExprClosure) -> ExprClosure {
+        closure
+    }
+
+    fn fold_expr_async(&mut self, async_block: ExprAsync) -> ExprAsync {
+        async_block
+    }
+
+    fn fold_item(&mut self, item: Item) -> Item {
+        item
+    }
+}
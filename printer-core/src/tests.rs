@@ -1,6 +1,6 @@
 #![cfg(test)]
 
-use crate::printer_core;
+use crate::{printer_core, printer_core_reporting};
 use quote::quote;
 
 #[test]
@@ -74,7 +74,7 @@ fn ink_before_each_test() {
     let after = printer_core(quote!(), before);
     assert_eq!(
         after.to_string(),
-        "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; Ok (()) } }"
+        "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; # [allow (unused_variables)] let constructor = FlipperRef :: new (false) ; # [allow (unused_variables)] let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; Ok (()) } const _ : () = { { # [deprecated (note = \"`constructor` shadows the binding declared in the hook (in before_each `setup`, inserted into test `default_works`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; const _ : () = { { # [deprecated (note = \"`contract_acc_id` shadows the binding declared in the hook (in before_each `setup`, inserted into test `default_works`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; }"
     );
 }
 
@@ -132,7 +132,7 @@ fn ink_double_before_each_hook_single_function_test() {
     let after = printer_core(quote!(), before);
     assert_eq!(
         after.to_string(),
-        "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; Ok (()) } }"
+        "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; Ok (()) } }"
     )
 }
 
@@ -191,7 +191,7 @@ fn ink_before_each_test2() {
     let after = printer_core(quote!(), before);
     assert_eq!(
         after.to_string(),
-        "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; Ok (()) } }"
+        "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; Ok (()) } }"
     );
 }
 
@@ -266,7 +266,7 @@ fn ink_test_after_each() {
     };
 
     let after = printer_core(quote!(), before);
-    assert_eq!(after.to_string(), "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; assert ! (1 == 1) ; println ! (\"After each test!\") ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; assert ! (1 == 1) ; println ! (\"After each test!\") ; Ok (()) } }");
+    assert_eq!(after.to_string(), "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; assert ! (1 == 1) ; println ! (\"After each test!\") ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; assert ! (1 == 1) ; println ! (\"After each test!\") ; Ok (()) } }");
 }
 
 #[test]
@@ -340,7 +340,7 @@ fn ink_test_invariant_attr() {
     };
 
     let after = printer_core(quote!(), before);
-    assert_eq!(after.to_string(), "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { assert ! (1 == 1) ; println ! (\"In front and at the end!\") ; let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; assert ! (1 == 1) ; println ! (\"In front and at the end!\") ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { assert ! (1 == 1) ; println ! (\"In front and at the end!\") ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; assert ! (1 == 1) ; println ! (\"In front and at the end!\") ; Ok (()) } }");
+    assert_eq!(after.to_string(), "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; assert ! (1 == 1) ; println ! (\"In front and at the end!\") ; let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; assert ! (1 == 1) ; println ! (\"In front and at the end!\") ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; assert ! (1 == 1) ; println ! (\"In front and at the end!\") ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; assert ! (1 == 1) ; println ! (\"In front and at the end!\") ; Ok (()) } }");
 }
 
 #[test]
@@ -422,7 +422,7 @@ fn ink_before_each_after_each_invariant_test() {
     };
 
     let after = printer_core(quote!(), before);
-    assert_eq!(after.to_string(), "# [cfg (all (test , feature = \"e2e-tests\"))] # [printer] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; println ! (\"Only at the beginning\") ; println ! (\"this goes in the front...\") ; assert ! (1 == 1) ; println ! (\"...and at the end!\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; println ! (\"this goes in the front...\") ; assert ! (1 == 1) ; println ! (\"...and at the end!\") ; println ! (\"At the end only\") ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; println ! (\"Only at the beginning\") ; println ! (\"this goes in the front...\") ; assert ! (1 == 1) ; println ! (\"...and at the end!\") ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; println ! (\"this goes in the front...\") ; assert ! (1 == 1) ; println ! (\"...and at the end!\") ; println ! (\"At the end only\") ; Ok (()) } }");
+    assert_eq!(after.to_string(), "# [cfg (all (test , feature = \"e2e-tests\"))] # [printer] mod e2e_tests { use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; println ! (\"Only at the beginning\") ; println ! (\"this goes in the front...\") ; assert ! (1 == 1) ; println ! (\"...and at the end!\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; println ! (\"this goes in the front...\") ; assert ! (1 == 1) ; println ! (\"...and at the end!\") ; println ! (\"At the end only\") ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; let constructor = FlipperRef :: new (false) ; # [allow (unused_variables)] let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; println ! (\"Only at the beginning\") ; println ! (\"this goes in the front...\") ; assert ! (1 == 1) ; println ! (\"...and at the end!\") ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; println ! (\"this goes in the front...\") ; assert ! (1 == 1) ; println ! (\"...and at the end!\") ; println ! (\"At the end only\") ; Ok (()) } const _ : () = { { # [deprecated (note = \"`contract_acc_id` shadows the binding declared in the hook (in before_each `setup`, inserted into test `default_works`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; }");
 }
 
 #[test]
@@ -452,7 +452,7 @@ fn regular_test_mod() {
     };
 
     let after = printer_core(quote!(), before);
-    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [test] fn sample_test () { println ! (\"This goes first!\") ; let c = 12 ; let a = 1 ; let b = 10 ; assert ! (a + b == 11) ; assert ! (a + b + c > 5) ; } }");
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [test] fn sample_test () { printer :: runtime :: check_reporting_env () ; println ! (\"This goes first!\") ; let c = 12 ; let a = 1 ; let b = 10 ; assert ! (a + b == 11) ; assert ! (a + b + c > 5) ; } }");
 }
 
 #[test]
@@ -529,7 +529,7 @@ fn ink_before_all_test() {
     };
 
     let after = printer_core(quote!(), before);
-    assert_eq!(after.to_string(), "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"This should be executed first!\") ; for _ in 0 .. 2usize { channel_s . send (true) . unwrap () ; } } use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; Ok (()) } }");
+    assert_eq!(after.to_string(), "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"This should be executed first!\") ; for _ in 0 .. 2usize { channel_s . send (true) . unwrap () ; } } use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } # [allow (unused_variables)] let constructor = FlipperRef :: new (false) ; # [allow (unused_variables)] let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; Ok (()) } const _ : () = { { # [deprecated (note = \"`constructor` shadows the binding declared in the hook (in before_each `setup`, inserted into test `default_works`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; const _ : () = { { # [deprecated (note = \"`contract_acc_id` shadows the binding declared in the hook (in before_each `setup`, inserted into test `default_works`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; }");
 }
 
 #[test]
@@ -645,6 +645,987 @@ fn printer_with_helper_functions_in_tests_mod() {
     };
 
     let after = printer_core(quote!(), before);
-    assert_eq!(after.to_string(), "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"This should be executed first!\") ; for _ in 0 .. 2usize { channel_s . send (true) . unwrap () ; } } use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; async fn helper_function () { println ! (\"Doing something here\") ; std :: thread :: sleep (std :: time :: Duration :: from_millis (100)) ; } # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; helper_function . await ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; Ok (()) } }");
+    assert_eq!(after.to_string(), "# [printer] # [cfg (all (test , feature = \"e2e-tests\"))] mod e2e_tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"This should be executed first!\") ; for _ in 0 .. 2usize { channel_s . send (true) . unwrap () ; } } use super :: * ; use ink_e2e :: build_message ; type E2EResult < T > = std :: result :: Result < T , Box < dyn std :: error :: Error > > ; async fn helper_function () { println ! (\"Doing something here\") ; std :: thread :: sleep (std :: time :: Duration :: from_millis (100)) ; } # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let constructor = FlipperRef :: new (false) ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; let flip = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . flip ()) ; let _flip_res = client . call (& ink_e2e :: bob () , flip , 0 , None) . await . expect (\"flip failed\") ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , true)) ; helper_function . await ; Ok (()) } # [ink_e2e :: test] async fn default_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } # [allow (unused_variables)] let constructor = FlipperRef :: new (false) ; # [allow (unused_variables)] let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: alice () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let constructor = FlipperRef :: new_default () ; let contract_acc_id = client . instantiate (\"flipper\" , & ink_e2e :: bob () , constructor , 0 , None) . await . expect (\"instantiate failed\") . account_id ; let get = build_message :: < FlipperRef > (contract_acc_id . clone ()) . call (| flipper | flipper . get ()) ; let get_res = client . call_dry_run (& ink_e2e :: bob () , & get , 0 , None) . await ; assert ! (matches ! (get_res . return_value () , false)) ; Ok (()) } const _ : () = { { # [deprecated (note = \"`constructor` shadows the binding declared in the hook (in before_each `setup`, inserted into test `default_works`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; const _ : () = { { # [deprecated (note = \"`contract_acc_id` shadows the binding declared in the hook (in before_each `setup`, inserted into test `default_works`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; }");
 }
 
+#[test]
+fn rstest_cases_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            use rstest::rstest;
+
+            #[before_all]
+            fn first() {
+                println!("This should be executed first!");
+            }
+
+            #[before_each]
+            fn setup() {
+                let expected = a + b;
+            }
+
+            #[rstest]
+            #[case(1, 2)]
+            #[case::negative(-1, -2)]
+            #[case(0, 0)]
+            fn add_works(#[case] a: i32, #[case] b: i32) {
+                assert_eq!(expected, b + a);
+            }
+
+            #[rstest]
+            fn sign_works(#[values(1, 2, 3)] a: i32, #[values(4, 5)] b: i32) {
+                assert!(expected > 0);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"This should be executed first!\") ; for _ in 0 .. 9usize { channel_s . send (true) . unwrap () ; } } use rstest :: rstest ; # [rstest] # [case (1 , 2)] # [case :: negative (- 1 , - 2)] # [case (0 , 0)] fn add_works (# [case] a : i32 , # [case] b : i32) { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let expected = a + b ; assert_eq ! (expected , b + a) ; } # [rstest] fn sign_works (# [values (1 , 2 , 3)] a : i32 , # [values (4 , 5)] b : i32) { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let expected = a + b ; assert ! (expected > 0) ; } }");
+}
+
+#[test]
+fn proptest_per_case_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            use proptest::prelude::*;
+
+            #[before_all]
+            fn first() {
+                println!("This should be executed first!");
+            }
+
+            #[before_each]
+            fn setup() {
+                let limit = 100u32;
+            }
+
+            #[after_each]
+            fn cleanup() {
+                println!("Case done");
+            }
+
+            proptest! {
+                #![proptest_config(ProptestConfig::with_cases(10))]
+
+                #[test]
+                fn below_limit(x in 0..100u32, y in 0..100u32) {
+                    prop_assert!(x < limit && y < limit);
+                }
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"This should be executed first!\") ; for _ in 0 .. 1usize { channel_s . send (true) . unwrap () ; } } use proptest :: prelude :: * ; proptest ! { # ! [proptest_config (ProptestConfig :: with_cases (10))] # [test] fn below_limit (x in 0 .. 100u32 , y in 0 .. 100u32) { { static PRINTER_SYNC_ONCE : std :: sync :: Once = std :: sync :: Once :: new () ; PRINTER_SYNC_ONCE . call_once (| | { while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } }) ; } let limit = 100u32 ; prop_assert ! (x < limit && y < limit) ; println ! (\"Case done\") ; } } }");
+}
+
+#[test]
+fn quickcheck_per_case_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            use quickcheck::quickcheck;
+
+            #[before_each]
+            fn setup() {
+                let offset = 1u64;
+            }
+
+            #[invariant]
+            fn always_holds() {
+                assert!(offset > 0);
+            }
+
+            quickcheck! {
+                fn addition_grows(x: u32) -> bool {
+                    x as u64 + offset > x as u64
+                }
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { use quickcheck :: quickcheck ; quickcheck ! { fn addition_grows (x : u32) -> bool { let offset = 1u64 ; assert ! (offset > 0) ; assert ! (offset > 0) ; x as u64 + offset > x as u64 } } }");
+}
+
+#[test]
+fn proptest_per_property_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer(property_hooks = per_property)]
+        mod tests {
+            use proptest::prelude::*;
+
+            #[before_each]
+            fn setup() {
+                let limit = 100u32;
+            }
+
+            #[after_each]
+            fn cleanup() {
+                println!("Property done");
+            }
+
+            proptest! {
+                #![proptest_config(ProptestConfig::with_cases(10))]
+
+                #[test]
+                fn below_limit(x in 0..100u32) {
+                    prop_assert!(x < limit);
+                }
+            }
+        }
+    };
+
+    let after = printer_core(quote!(property_hooks = per_property), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer (property_hooks = per_property)] mod tests { use proptest :: prelude :: * ; # [test] fn below_limit () { let limit = 100u32 ; proptest ! (ProptestConfig :: with_cases (10) , | (x in 0 .. 100u32) | { prop_assert ! (x < limit) ; }) ; println ! (\"Property done\") ; } }");
+}
+
+#[test]
+#[should_panic]
+fn quickcheck_per_property() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer(property_hooks = per_property)]
+        mod tests {
+            use quickcheck::quickcheck;
+
+            #[before_each]
+            fn setup() {
+                let offset = 1u64;
+            }
+
+            quickcheck! {
+                fn addition_grows(x: u32) -> bool {
+                    x as u64 + offset > x as u64
+                }
+            }
+        }
+    };
+
+    let _after = printer_core(quote!(property_hooks = per_property), before);
+}
+
+#[test]
+fn each_rows_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_all]
+            fn first() {
+                println!("This should be executed first!");
+            }
+
+            #[before_each(only)]
+            fn setup() {
+                println!("Testing {:?}", sender);
+            }
+
+            #[each(alice, bob(), charlie)]
+            #[test]
+            fn transfer_works(sender: &str) {
+                assert!(!sender.is_empty());
+            }
+
+            #[test]
+            #[each((1, 2, 3), (-1, -2, -3))]
+            fn add_works(a: i32, b: i32, expected: i32) {
+                assert_eq!(a + b, expected);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"This should be executed first!\") ; for _ in 0 .. 5usize { channel_s . send (true) . unwrap () ; } } # [test] fn transfer_works_alice () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let sender : & str = alice ; println ! (\"Testing {:?}\" , sender) ; assert ! (! sender . is_empty ()) ; } # [test] fn transfer_works_bob () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let sender : & str = bob () ; println ! (\"Testing {:?}\" , sender) ; assert ! (! sender . is_empty ()) ; } # [test] fn transfer_works_charlie () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let sender : & str = charlie ; println ! (\"Testing {:?}\" , sender) ; assert ! (! sender . is_empty ()) ; } # [test] fn add_works_1_2_3 () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let a : i32 = 1 ; let b : i32 = 2 ; let expected : i32 = 3 ; assert_eq ! (a + b , expected) ; } # [test] fn add_works_minus_1_minus_2_minus_3 () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let a : i32 = - 1 ; let b : i32 = - 2 ; let expected : i32 = - 3 ; assert_eq ! (a + b , expected) ; } }");
+}
+
+#[test]
+#[should_panic]
+fn each_row_too_long() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[each((1, 2), (3, 4))]
+            #[test]
+            fn single_param(a: i32) {
+                assert!(a > 0);
+            }
+        }
+    };
+
+    let _after = printer_core(quote!(), before);
+}
+
+#[test]
+fn matrix_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer(matrix(caller = [alice, bob], ctor = [Flipper::new(false), Flipper::new_default()]))]
+        mod tests {
+            use super::*;
+
+            #[before_all]
+            fn first() {
+                println!("Setting up for {:?}", caller);
+            }
+
+            #[before_each]
+            fn setup() {
+                let flipper = ctor;
+            }
+
+            fn helper() -> bool {
+                true
+            }
+
+            #[test]
+            fn it_works() {
+                assert!(helper());
+                assert!(flipper.flip(caller));
+            }
+        }
+    };
+
+    let after = printer_core(
+        quote!(matrix(
+            caller = [alice, bob],
+            ctor = [Flipper::new(false), Flipper::new_default()]
+        )),
+        before,
+    );
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer (matrix (caller = [alice , bob] , ctor = [Flipper :: new (false) , Flipper :: new_default ()]))] mod tests { use super :: * ; fn helper () -> bool { true } mod caller_alice_ctor_flipper_new_false { use super :: * ; use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; # [allow (unused_variables)] let caller = alice ; # [allow (unused_variables)] let ctor = Flipper :: new (false) ; println ! (\"Setting up for {:?}\" , caller) ; for _ in 0 .. 1usize { channel_s . send (true) . unwrap () ; } } # [test] fn it_works () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } # [allow (unused_variables)] let caller = alice ; # [allow (unused_variables)] let ctor = Flipper :: new (false) ; let flipper = ctor ; assert ! (helper ()) ; assert ! (flipper . flip (caller)) ; } } mod caller_alice_ctor_flipper_new_default { use super :: * ; use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; # [allow (unused_variables)] let caller = alice ; # [allow (unused_variables)] let ctor = Flipper :: new_default () ; println ! (\"Setting up for {:?}\" , caller) ; for _ in 0 .. 1usize { channel_s . send (true) . unwrap () ; } } # [test] fn it_works () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } # [allow (unused_variables)] let caller = alice ; # [allow (unused_variables)] let ctor = Flipper :: new_default () ; let flipper = ctor ; assert ! (helper ()) ; assert ! (flipper . flip (caller)) ; } } mod caller_bob_ctor_flipper_new_false { use super :: * ; use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; # [allow (unused_variables)] let caller = bob ; # [allow (unused_variables)] let ctor = Flipper :: new (false) ; println ! (\"Setting up for {:?}\" , caller) ; for _ in 0 .. 1usize { channel_s . send (true) . unwrap () ; } } # [test] fn it_works () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } # [allow (unused_variables)] let caller = bob ; # [allow (unused_variables)] let ctor = Flipper :: new (false) ; let flipper = ctor ; assert ! (helper ()) ; assert ! (flipper . flip (caller)) ; } } mod caller_bob_ctor_flipper_new_default { use super :: * ; use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; # [allow (unused_variables)] let caller = bob ; # [allow (unused_variables)] let ctor = Flipper :: new_default () ; println ! (\"Setting up for {:?}\" , caller) ; for _ in 0 .. 1usize { channel_s . send (true) . unwrap () ; } } # [test] fn it_works () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } # [allow (unused_variables)] let caller = bob ; # [allow (unused_variables)] let ctor = Flipper :: new_default () ; let flipper = ctor ; assert ! (helper ()) ; assert ! (flipper . flip (caller)) ; } } }");
+}
+
+#[test]
+#[should_panic]
+fn matrix_without_array() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer(matrix(caller = alice))]
+        mod tests {
+            #[test]
+            fn it_works() {
+                assert!(caller.is_empty());
+            }
+        }
+    };
+
+    let _after = printer_core(quote!(matrix(caller = alice)), before);
+}
+
+#[test]
+fn only_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_all]
+            fn first() {
+                println!("This should be executed first!");
+            }
+
+            #[test]
+            #[only]
+            fn focused() {
+                assert!(true);
+            }
+
+            #[test]
+            fn not_focused() {
+                assert!(true);
+            }
+
+            #[test]
+            #[ignore]
+            fn already_ignored() {
+                assert!(true);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"This should be executed first!\") ; for _ in 0 .. 1usize { channel_s . send (true) . unwrap () ; } } # [test] fn focused () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } { # [deprecated (note = \"`#[only]` is set on this test, remove it before committing\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; assert ! (true) ; } # [test] # [ignore = \"not focused with #[only]\"] fn not_focused () { assert ! (true) ; } # [test] # [ignore] fn already_ignored () { assert ! (true) ; } }");
+}
+
+#[test]
+fn pending_tests_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                println!("Not in pending tests");
+            }
+
+            #[test]
+            fn it_works() {
+                assert!(true);
+            }
+
+            #[skip("blocked on node upgrade")]
+            #[test]
+            fn upgrade_works() {
+                assert!(false);
+            }
+
+            /// Reentrancy is not handled yet.
+            #[todo]
+            fn handles_reentrancy();
+
+            #[todo("needs a second contract")]
+            #[test]
+            fn cross_contract_call_works() {
+                assert!(false);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { static PRINTER_SUITE : printer :: runtime :: Suite = printer :: runtime :: Suite :: new (module_path ! () , & [(\"it_works\" , 1usize , false)] , & [(\"upgrade_works\" , \"blocked on node upgrade\") , (\"handles_reentrancy\" , \"todo\") , (\"cross_contract_call_works\" , \"todo: needs a second contract\")] , false ,) ; # [test] fn it_works () { let _printer_test = PRINTER_SUITE . enter (\"it_works\") ; println ! (\"Not in pending tests\") ; assert ! (true) ; } # [test] # [ignore = \"blocked on node upgrade\"] fn upgrade_works () { assert ! (false) ; } # [doc = r\" Reentrancy is not handled yet.\"] # [test] # [ignore = \"todo\"] fn handles_reentrancy () { } # [test] # [ignore = \"todo: needs a second contract\"] fn cross_contract_call_works () { assert ! (false) ; } }");
+}
+
+#[test]
+fn all_pending_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[todo]
+            fn handles_reentrancy();
+
+            #[skip("blocked on node upgrade")]
+            #[test]
+            fn upgrade_works() {}
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { static PRINTER_SUITE : printer :: runtime :: Suite = printer :: runtime :: Suite :: new (module_path ! () , & [] , & [(\"handles_reentrancy\" , \"todo\") , (\"upgrade_works\" , \"blocked on node upgrade\")] , false ,) ; # [test] # [ignore = \"todo\"] fn handles_reentrancy () { } # [test] # [ignore = \"blocked on node upgrade\"] fn upgrade_works () { } # [test] fn printer_pending () { PRINTER_SUITE . finish_pending () ; } }");
+}
+
+#[test]
+fn runtime_skip_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                if std::env::var("CONTRACTS_NODE").is_err() {
+                    printer::skip!("CONTRACTS_NODE is not set");
+                }
+            }
+
+            #[after_each]
+            fn teardown() {
+                println!("Teardown");
+            }
+
+            #[test]
+            fn it_works() {
+                let values = vec![1, 2].into_iter().map(|value| {
+                    skip!();
+                    value
+                });
+                skip!("{} values", values.count());
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { static PRINTER_SUITE : printer :: runtime :: Suite = printer :: runtime :: Suite :: new (module_path ! () , & [(\"it_works\" , 1usize , false)] , & [] , false ,) ; # [test] fn it_works () { let _printer_test = PRINTER_SUITE . enter (\"it_works\") ; if std :: env :: var (\"CONTRACTS_NODE\") . is_err () { { println ! (\"Teardown\") ; printer :: skip ! (\"CONTRACTS_NODE is not set\") } ; } let values = vec ! [1 , 2] . into_iter () . map (| value | { skip ! () ; value }) ; { println ! (\"Teardown\") ; skip ! (\"{} values\" , values . count ()) } ; println ! (\"Teardown\") ; } }");
+}
+
+#[test]
+fn timeout_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[after_each]
+            fn teardown() {
+                println!("Teardown");
+            }
+
+            #[timeout("1m30s")]
+            #[tokio::test]
+            async fn it_works() -> Result<(), String> {
+                Ok(())
+            }
+
+            #[test]
+            fn sync_works() {
+                assert!(true);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(timeout = "500ms"), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [tokio :: test] async fn it_works () -> Result < () , String > { printer :: runtime :: check_reporting_env () ; let printer_timeout_start = std :: time :: Instant :: now () ; match printer :: tokio :: time :: timeout (std :: time :: Duration :: from_millis (90000u64) , async { println ! (\"Teardown\") ; Ok (()) } ,) . await { Ok (printer_result) => printer_result , Err (_) => { let printer_elapsed = printer_timeout_start . elapsed () ; println ! (\"Teardown\") ; printer :: runtime :: timed_out (\"it_works\" , printer_elapsed) } } } # [test] fn sync_works () { printer :: runtime :: check_reporting_env () ; match printer :: runtime :: run_with_timeout (\"sync_works\" , std :: time :: Duration :: from_millis (500u64) , move | | -> () { assert ! (true) ; println ! (\"Teardown\") ; } ,) { Ok (printer_result) => printer_result , Err (printer_elapsed) => { println ! (\"Teardown\") ; printer :: runtime :: timed_out (\"sync_works\" , printer_elapsed) } } ; } }");
+}
+
+#[test]
+#[should_panic]
+fn timeout_unknown_unit() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[timeout("30 seconds")]
+            #[test]
+            fn it_works() {}
+        }
+    };
+
+    printer_core(quote!(), before);
+}
+
+#[test]
+fn retries_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                println!("Setup");
+            }
+
+            #[retries(3)]
+            #[tokio::test]
+            async fn it_works() -> Result<(), String> {
+                Ok(())
+            }
+
+            #[test]
+            fn sync_works() {
+                assert!(true);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(retries = 1), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [tokio :: test] async fn it_works () -> Result < () , String > { printer :: runtime :: check_reporting_env () ; let mut printer_attempts = printer :: runtime :: Attempts :: new (\"it_works\" , 3usize) ; loop { let printer_attempt : std :: thread :: Result < Result < () , String > > = printer :: runtime :: catch_unwind (async { println ! (\"Setup\") ; Ok (()) }) . await ; if let Some (printer_result) = printer_attempts . check (printer_attempt) { break printer_result ; } } } # [test] fn sync_works () { printer :: runtime :: check_reporting_env () ; let mut printer_attempts = printer :: runtime :: Attempts :: new (\"sync_works\" , 1usize) ; loop { let printer_attempt : std :: thread :: Result < () > = std :: panic :: catch_unwind (std :: panic :: AssertUnwindSafe (| | { println ! (\"Setup\") ; assert ! (true) ; })) ; if let Some (printer_result) = printer_attempts . check (printer_attempt) { break printer_result ; } } } }");
+}
+
+#[test]
+fn slow_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                println!("Setup");
+            }
+
+            #[after_each]
+            fn teardown() {
+                println!("Teardown");
+            }
+
+            #[slow("2s")]
+            #[test]
+            fn it_works() -> Result<(), String> {
+                Ok(())
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [test] fn it_works () -> Result < () , String > { printer :: runtime :: check_reporting_env () ; let mut printer_slow = printer :: runtime :: SlowTimer :: start (\"it_works\" , std :: time :: Duration :: from_millis (2000u64) ,) ; println ! (\"Setup\") ; printer_slow . body_started () ; printer_slow . body_finished () ; println ! (\"Teardown\") ; printer_slow . finish () ; Ok (()) } }");
+}
+
+#[test]
+fn serial_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                println!("Setup");
+            }
+
+            #[test]
+            fn it_works() {
+                assert!(true);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(serial, matrix(caller = [alice(), bob()])), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { static PRINTER_SEMAPHORE : printer :: runtime :: Semaphore = printer :: runtime :: Semaphore :: new (1usize) ; mod caller_alice { use super :: * ; # [test] fn it_works () { printer :: runtime :: check_reporting_env () ; let _printer_permit = PRINTER_SEMAPHORE . acquire () ; # [allow (unused_variables)] let caller = alice () ; println ! (\"Setup\") ; assert ! (true) ; } } mod caller_bob { use super :: * ; # [test] fn it_works () { printer :: runtime :: check_reporting_env () ; let _printer_permit = PRINTER_SEMAPHORE . acquire () ; # [allow (unused_variables)] let caller = bob () ; println ! (\"Setup\") ; assert ! (true) ; } } }");
+}
+
+#[test]
+#[should_panic]
+fn serial_with_max_parallel() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[test]
+            fn it_works() {}
+        }
+    };
+
+    printer_core(quote!(serial, max_parallel = 2), before);
+}
+
+#[test]
+#[should_panic]
+fn duplicate_max_parallel() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[test]
+            fn it_works() {}
+        }
+    };
+
+    printer_core(quote!(max_parallel = 2, max_parallel = 3), before);
+}
+
+#[test]
+fn exclusive_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[exclusive(node)]
+            #[before_each]
+            fn setup() {
+                println!("Setup");
+            }
+
+            #[before_all]
+            #[exclusive(faucet)]
+            fn airdrop() {
+                println!("Airdrop");
+            }
+
+            #[exclusive(port, account, node)]
+            #[test]
+            fn it_works() {
+                assert!(true);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { use printer :: once_cell :: sync :: OnceCell ; use printer :: tokio ; use printer :: crossbeam_channel :: { unbounded , Sender , Receiver } ; static PRINTER_SYNC_CHANNEL : OnceCell < (Sender < bool > , Receiver < bool >) > = OnceCell :: new () ; # [tokio :: test] async fn printer_before_all () { let _printer_resources = printer :: runtime :: lock_resources (& [\"faucet\"]) ; let (channel_s , _channel_recv) = PRINTER_SYNC_CHANNEL . get_or_init (| | unbounded ()) ; println ! (\"Airdrop\") ; for _ in 0 .. 1usize { channel_s . send (true) . unwrap () ; } } # [test] fn it_works () { printer :: runtime :: check_reporting_env () ; while true { if let Some (channel) = PRINTER_SYNC_CHANNEL . get () { let this_receiver = channel . 1 . clone () ; let ready = this_receiver . recv () . unwrap () ; assert ! (ready) ; break ; } else { std :: thread :: sleep (std :: time :: Duration :: from_millis (200)) ; } } let _printer_resources = printer :: runtime :: lock_resources (& [\"account\" , \"node\" , \"port\"]) ; println ! (\"Setup\") ; assert ! (true) ; } }");
+}
+
+#[test]
+fn sequential_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[test]
+            fn deploy_works() {
+                assert!(true);
+            }
+
+            #[tokio::test]
+            async fn upgrade_works() -> Result<(), String> {
+                Ok(())
+            }
+
+            #[test]
+            fn migrate_works() -> Result<(), String> {
+                Ok(())
+            }
+        }
+    };
+
+    let after = printer_core(quote!(sequential), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { static PRINTER_SUITE : printer :: runtime :: Suite = printer :: runtime :: Suite :: new (module_path ! () , & [(\"deploy_works\" , 1usize , false) , (\"upgrade_works\" , 1usize , false) , (\"migrate_works\" , 1usize , false)] , & [] , false ,) ; static PRINTER_COMPLETIONS : printer :: runtime :: Completions = printer :: runtime :: Completions :: new (module_path ! () , & [(\"deploy_works\" , 1usize , false , & []) , (\"upgrade_works\" , 1usize , false , & [(0usize , false)]) , (\"migrate_works\" , 1usize , false , & [(1usize , false)])] ,) ; # [test] fn deploy_works () { let _printer_test = PRINTER_SUITE . enter (\"deploy_works\") ; let _printer_completion = match PRINTER_COMPLETIONS . wait (0usize) { Ok (printer_completion) => printer_completion , Err (printer_reason) => printer :: skip ! (\"{}\" , printer_reason) , } ; assert ! (true) ; } # [tokio :: test] async fn upgrade_works () -> Result < () , String > { let printer_test = PRINTER_SUITE . enter_fallible (\"upgrade_works\") ; let _printer_completion = match PRINTER_COMPLETIONS . wait (1usize) { Ok (printer_completion) => printer_completion , Err (printer_reason) => printer :: skip ! (\"{}\" , printer_reason) , } ; { let printer_result : Result < () , String > = Ok (()) ; printer_test . check (& printer_result) ; printer_result } } # [test] fn migrate_works () -> Result < () , String > { let printer_test = PRINTER_SUITE . enter_fallible (\"migrate_works\") ; let _printer_completion = match PRINTER_COMPLETIONS . wait (2usize) { Ok (printer_completion) => printer_completion , Err (printer_reason) => printer :: skip ! (\"{}\" , printer_reason) , } ; { let printer_result : Result < () , String > = Ok (()) ; printer_test . check (& printer_result) ; printer_result } } }");
+}
+
+#[test]
+fn depends_on_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[test]
+            fn deploy_works() {
+                assert!(true);
+            }
+
+            #[test]
+            fn independent_works() {
+                assert!(true);
+            }
+
+            #[depends_on(deploy_works, cross_contract_call_works)]
+            #[test]
+            fn upgrade_works() {
+                assert!(true);
+            }
+
+            #[todo]
+            fn cross_contract_call_works();
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { static PRINTER_SUITE : printer :: runtime :: Suite = printer :: runtime :: Suite :: new (module_path ! () , & [(\"deploy_works\" , 1usize , false) , (\"independent_works\" , 1usize , false) , (\"upgrade_works\" , 1usize , false)] , & [(\"cross_contract_call_works\" , \"todo\")] , false ,) ; static PRINTER_COMPLETIONS : printer :: runtime :: Completions = printer :: runtime :: Completions :: new (module_path ! () , & [(\"deploy_works\" , 1usize , false , & []) , (\"upgrade_works\" , 1usize , false , & [(0usize , true) , (2usize , true)]) , (\"cross_contract_call_works\" , 0usize , true , & [])] ,) ; # [test] fn deploy_works () { let _printer_test = PRINTER_SUITE . enter (\"deploy_works\") ; let _printer_completion = match PRINTER_COMPLETIONS . wait (0usize) { Ok (printer_completion) => printer_completion , Err (printer_reason) => printer :: skip ! (\"{}\" , printer_reason) , } ; assert ! (true) ; } # [test] fn independent_works () { let _printer_test = PRINTER_SUITE . enter (\"independent_works\") ; assert ! (true) ; } # [test] fn upgrade_works () { let _printer_test = PRINTER_SUITE . enter (\"upgrade_works\") ; let _printer_completion = match PRINTER_COMPLETIONS . wait (1usize) { Ok (printer_completion) => printer_completion , Err (printer_reason) => printer :: skip ! (\"{}\" , printer_reason) , } ; assert ! (true) ; } # [test] # [ignore = \"todo\"] fn cross_contract_call_works () { } }");
+}
+
+#[test]
+#[should_panic]
+fn depends_on_unknown_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[depends_on(deploy_works)]
+            #[test]
+            fn upgrade_works() {}
+        }
+    };
+
+    printer_core(quote!(), before);
+}
+
+#[test]
+#[should_panic]
+fn depends_on_cycle() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[depends_on(upgrade_works)]
+            #[test]
+            fn deploy_works() {}
+
+            #[depends_on(deploy_works)]
+            #[test]
+            fn upgrade_works() {}
+        }
+    };
+
+    printer_core(quote!(), before);
+}
+
+#[test]
+fn bail_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[tokio::test]
+            async fn it_works() -> Result<(), String> {
+                Ok(())
+            }
+        }
+    };
+
+    let after = printer_core(quote!(bail), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { static PRINTER_SUITE : printer :: runtime :: Suite = printer :: runtime :: Suite :: new (module_path ! () , & [(\"it_works\" , 1usize , false)] , & [] , true ,) ; # [tokio :: test] async fn it_works () -> Result < () , String > { let printer_test = PRINTER_SUITE . enter_fallible (\"it_works\") ; if let Some (printer_reason) = printer_test . bail_reason () { printer :: skip ! (\"{}\" , printer_reason) ; } { let printer_result : Result < () , String > = Ok (()) ; printer_test . check (& printer_result) ; printer_result } } }");
+}
+
+#[test]
+fn reporting_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                let flipper = Flipper::new(false);
+            }
+
+            #[test]
+            fn it_works() {
+                assert!(flipper.get());
+            }
+
+            #[test]
+            fn it_returns() -> Result<(), String> {
+                let checks = || -> Result<(), String> { return Ok(()); };
+                if flipper.get() {
+                    return Err("flipped".to_string());
+                }
+                checks()?;
+                Ok(())
+            }
+        }
+    };
+
+    let after = printer_core_reporting(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { static PRINTER_SUITE : printer :: runtime :: Suite = printer :: runtime :: Suite :: new (module_path ! () , & [(\"it_works\" , 1usize , false) , (\"it_returns\" , 1usize , false)] , & [] , false ,) ; # [test] fn it_works () { let printer_test = PRINTER_SUITE . enter (\"it_works\") ; if printer_test . is_grepped_out () { return printer :: runtime :: Skipped :: skipped () ; } if let Some (printer_reason) = printer_test . bail_reason () { printer :: skip ! (\"{}\" , printer_reason) ; } printer :: runtime :: hook_started (\"before each\" , \"setup\") ; let flipper = Flipper :: new (false) ; printer :: runtime :: hook_finished () ; assert ! (flipper . get ()) ; } # [test] fn it_returns () -> Result < () , String > { let printer_test = PRINTER_SUITE . enter_fallible (\"it_returns\") ; if printer_test . is_grepped_out () { return printer :: runtime :: Skipped :: skipped () ; } if let Some (printer_reason) = printer_test . bail_reason () { printer :: skip ! (\"{}\" , printer_reason) ; } printer :: runtime :: hook_started (\"before each\" , \"setup\") ; let flipper = Flipper :: new (false) ; printer :: runtime :: hook_finished () ; let checks = | | -> Result < () , String > { return Ok (()) ; } ; if flipper . get () { return { let printer_result : Result < () , String > = Err (\"flipped\" . to_string ()) ; printer_test . check (& printer_result) ; printer_result } ; } checks () ? ; { let printer_result : Result < () , String > = Ok (()) ; printer_test . check (& printer_result) ; printer_result } } }");
+}
+
+#[test]
+fn expand_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                let flipper = Flipper::new(false);
+            }
+
+            #[after_each]
+            fn teardown() {
+                drop(flipper);
+            }
+
+            #[test]
+            fn it_works() {
+                assert!(!flipper.get());
+            }
+        }
+    };
+
+    let module = syn::parse2(before).unwrap();
+    let after = crate::expand_mod_item(module, &crate::args::PrinterArgs::parse(quote!()), true);
+    assert_eq!(crate::expand::render_expansion(&after, false), "#[cfg(test)]\n#[printer]\nmod tests {\n    #[test]\n    fn it_works() {\n        printer::runtime::check_reporting_env();\n        // start of \"before each\" hook: setup\n        let flipper = Flipper::new(false);\n        // end of \"before each\" hook: setup\n        assert!(! flipper.get());\n        // start of \"after each\" hook: teardown\n        drop(flipper);\n        // end of \"after each\" hook: teardown\n    }\n}\n");
+}
+
+#[test]
+fn expand_unchanged_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                let flipper = Flipper::new(false);
+            }
+
+            #[test]
+            fn it_works() {
+                assert!(!flipper.get());
+            }
+        }
+    };
+
+    // `expand` writes the expansion like `PRINTER_EXPAND=1`
+    let expanded = printer_core(quote!(expand), before.clone());
+    assert_eq!(
+        expanded.to_string(),
+        printer_core(quote!(), before).to_string()
+    );
+}
+
+#[test]
+fn hook_return_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                if !ready() {
+                    return;
+                }
+            }
+
+            #[test]
+            fn it_works() {}
+
+            #[test]
+            fn it_fails() {}
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [test] fn it_works () { printer :: runtime :: check_reporting_env () ; if ! ready () { return ; } } # [test] fn it_fails () { printer :: runtime :: check_reporting_env () ; if ! ready () { return ; } } }");
+}
+
+#[test]
+fn shadow_lint_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                let flipper = Flipper::new(false);
+                let unused = 1;
+                #[printer::allow(shadow)]
+                let quiet = 2;
+            }
+
+            #[after_each]
+            fn teardown() {
+                assert!(flipper.get());
+            }
+
+            #[test]
+            fn it_shadows() {
+                let flipper = Flipper::new(true);
+            }
+
+            #[test]
+            fn it_moves() {
+                flip(flipper);
+            }
+
+            #[test]
+            #[printer::allow(shadow)]
+            #[allow(dead_code)]
+            fn it_is_allowed() {
+                let flipper = Flipper::new(true);
+            }
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [test] fn it_shadows () { printer :: runtime :: check_reporting_env () ; # [allow (unused_variables)] let flipper = Flipper :: new (false) ; # [allow (unused_variables)] let unused = 1 ; # [allow (unused_variables)] let quiet = 2 ; let flipper = Flipper :: new (true) ; assert ! (flipper . get ()) ; } # [test] fn it_moves () { printer :: runtime :: check_reporting_env () ; let flipper = Flipper :: new (false) ; # [allow (unused_variables)] let unused = 1 ; # [allow (unused_variables)] let quiet = 2 ; flip (flipper) ; assert ! (flipper . get ()) ; } # [test] # [allow (dead_code)] fn it_is_allowed () { printer :: runtime :: check_reporting_env () ; # [allow (unused_variables)] let flipper = Flipper :: new (false) ; # [allow (unused_variables)] let unused = 1 ; # [allow (unused_variables)] let quiet = 2 ; let flipper = Flipper :: new (true) ; assert ! (flipper . get ()) ; } const _ : () = { { # [deprecated (note = \"`flipper` shadows the binding declared in the hook (in before_each `setup`, inserted into test `it_shadows`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; const _ : () = { { # [deprecated (note = \"`flipper` is the binding declared by the test, which shadows the one declared in before_each `setup` (in after_each `teardown`, inserted into test `it_shadows`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; const _ : () = { { # [deprecated (note = \"`unused` is not used by the test or by its hooks (in before_each `setup`, inserted into tests `it_shadows`, `it_moves`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; const _ : () = { { # [deprecated (note = \"`flipper` is moved by the test before the hook uses it, unless it is `Copy` (in after_each `teardown`, inserted into test `it_moves`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; }");
+}
+
+#[test]
+#[should_panic]
+fn hook_missing_param() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                let balance = client.balance();
+            }
+
+            #[ink_e2e::test]
+            async fn it_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+                Ok(())
+            }
+
+            #[test]
+            fn it_fails() {}
+        }
+    };
+
+    printer_core(quote!(), before);
+}
+
+#[test]
+fn only_hook_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each(only)]
+            fn setup() {
+                let balance = client.balance();
+            }
+
+            #[ink_e2e::test]
+            async fn it_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+                assert_eq!(balance, 0);
+                Ok(())
+            }
+
+            #[test]
+            fn it_fails() {}
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [ink_e2e :: test] async fn it_works (mut client : ink_e2e :: Client < C , E >) -> E2EResult < () > { printer :: runtime :: check_reporting_env () ; let balance = client . balance () ; assert_eq ! (balance , 0) ; Ok (()) } # [test] fn it_fails () { printer :: runtime :: check_reporting_env () ; } }");
+}
+
+#[test]
+fn hook_macro_field_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                let x = 1;
+                assert_eq!(cfg.client, x);
+            }
+
+            #[test]
+            fn it_works(client: u32) {}
+
+            #[test]
+            fn it_checks() {}
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [test] fn it_works (client : u32) { printer :: runtime :: check_reporting_env () ; let x = 1 ; assert_eq ! (cfg . client , x) ; } # [test] fn it_checks () { printer :: runtime :: check_reporting_env () ; let x = 1 ; assert_eq ! (cfg . client , x) ; } }");
+}
+
+#[test]
+fn hook_macro_guess_test() {
+    let before = quote! {
+        #[cfg(test)]
+        #[printer]
+        mod tests {
+            #[before_each]
+            fn setup() {
+                let clients = vec![client; 2];
+                assert_eq!(clients.len(), 2);
+            }
+
+            #[test]
+            fn it_works(client: u32) {}
+
+            #[test]
+            fn it_checks() {}
+        }
+    };
+
+    let after = printer_core(quote!(), before);
+    assert_eq!(after.to_string(), "# [cfg (test)] # [printer] mod tests { # [test] fn it_works (client : u32) { printer :: runtime :: check_reporting_env () ; let clients = vec ! [client ; 2] ; assert_eq ! (clients . len () , 2) ; } # [test] fn it_checks () { printer :: runtime :: check_reporting_env () ; let clients = vec ! [client ; 2] ; assert_eq ! (clients . len () , 2) ; } const _ : () = { { # [deprecated (note = \"the macro may use `client`, which is a parameter of other tests only (in before_each `setup`, inserted into test `it_checks`)\")] const PRINTER_WARNING : () = () ; PRINTER_WARNING } ; } ; }");
+}
+
+#[test]
+fn expansion_path_test() {
+    let file: syn::File = syn::parse_str(
+        "
+mod first {
+    #[printer]
+    mod tests {}
+}
+
+mod second {
+    #[printer]
+    mod tests {}
+}
+",
+    )
+    .unwrap();
+    let modules: Vec<syn::ItemMod> = file
+        .items
+        .into_iter()
+        .map(|item| match item {
+            syn::Item::Mod(outer) => match outer.content.unwrap().1.remove(0) {
+                syn::Item::Mod(module) => module,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        })
+        .collect();
+
+    let paths: Vec<std::path::PathBuf> = modules
+        .iter()
+        .map(|module| crate::expand::expansion_path("flipper", std::path::Path::new("# [cfg (test)] # [printer] mod tests { # [test] fn it_works (client : u32) { printer :: runtime :: check_reporting_env () ; let x = 1 ; assert_eq ! (cfg . client , x) ; } # [test] fn it_checks () { printer :: runtime :: check_reporting_env () ; let x = 1 ; assert_eq ! (cfg . client , x) ; } }"), module))
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            std::path::PathBuf::from("flipper/tests-4.rs"),
+            std::path::PathBuf::from("flipper/tests-9.rs")
+        ]
+    );
+}
@@ -0,0 +1,180 @@
+use proc_macro_error::abort;
+use syn::{parse_quote, Expr, FnArg, Ident, ItemFn, Lit, LitStr, Stmt};
+
+use crate::hook_events;
+use crate::lint::{let_bindings, pat_idents, uses};
+use crate::utils::{count_test_cases, take_attr, HooksData};
+
+/// Parses a duration like `"30s"`, `"500ms"` or `"1m30s"` into milliseconds. The supported units
+/// are `ms`, `s`, `m` and `h`.
+pub fn parse_duration(lit: &LitStr) -> u64 {
+    let value = lit.value();
+    let mut millis: u64 = 0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        abort!(lit, "the duration should not be empty");
+    }
+
+    while !rest.is_empty() {
+        let digits_end = rest
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_end = rest[digits_end..]
+            .find(|ch: char| ch.is_ascii_digit())
+            .map_or(rest.len(), |idx| digits_end + idx);
+
+        let Ok(number) = rest[..digits_end].parse::<u64>() else {
+            abort!(
+                lit,
+                "invalid duration `{}`, expected e.g. `30s` or `1m30s`",
+                value
+            );
+        };
+        let unit_millis = match &rest[digits_end..unit_end] {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            unit => abort!(
+                lit,
+                "unknown duration unit `{}`, expected `ms`, `s`, `m` or `h`",
+                unit
+            ),
+        };
+
+        millis += number * unit_millis;
+        rest = &rest[unit_end..];
+    }
+
+    millis
+}
+
+/// Parses the duration of a macro argument, e.g. `timeout = "30s"`.
+pub fn parse_duration_arg(name: &str, value: &Expr) -> u64 {
+    match value {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => parse_duration(lit),
+        _ => abort!(
+            value,
+            "`{}` should be a string, e.g. `{} = \"30s\"`",
+            name,
+            name
+        ),
+    }
+}
+
+/// Removes the attribute with a duration, e.g. `#[timeout("30s")]`, from the test and returns the
+/// duration in milliseconds.
+pub fn take_duration_attr(func: &mut ItemFn, name: &str) -> Option<u64> {
+    let attr = take_attr(&mut func.attrs, name)?;
+    if count_test_cases(func).is_none() {
+        abort!(attr, "`{}` should be used on test functions", name);
+    }
+
+    match attr.parse_args::<LitStr>() {
+        Ok(lit) => Some(parse_duration(&lit)),
+        Err(e) => abort!(
+            e.span(),
+            "`{}` should contain a duration, e.g. `#[{}(\"30s\")]`: {}",
+            name,
+            name,
+            e
+        ),
+    }
+}
+
+/// Wraps the body of the test, after the hooks are inserted, so that it fails once it runs longer
+/// than `timeout_millis`. On expiry, the `after_each` hooks which don't use the variables of the
+/// body, nor the parameters of a sync test moved to its thread, are executed before it fails.
+pub fn insert_timeout(function: &mut ItemFn, timeout_millis: u64, hooks_data: &HooksData) {
+    let name = function.sig.ident.to_string();
+    let stmts = std::mem::take(&mut function.block.stmts);
+    let return_type: syn::Type = match &function.sig.output {
+        syn::ReturnType::Default => parse_quote!(()),
+        syn::ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+
+    let mut unreachable: Vec<String> = stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Local(local) => Some(pat_idents(&local.pat)),
+            _ => None,
+        })
+        .flatten()
+        .map(|ident| ident.to_string())
+        .collect();
+    if function.sig.asyncness.is_none() {
+        unreachable.extend(
+            function
+                .sig
+                .inputs
+                .iter()
+                .filter_map(|input| match input {
+                    FnArg::Typed(pat_type) => Some(pat_idents(&pat_type.pat)),
+                    FnArg::Receiver(_) => None,
+                })
+                .flatten()
+                .map(|ident| ident.to_string()),
+        );
+    }
+    let after_each = hook_events("after each", &hooks_data.after_each, hooks_data.events);
+    let teardown: Vec<Stmt> = hooks_data
+        .after_each
+        .iter()
+        .zip(after_each)
+        .filter(|(hook, _)| {
+            let own: Vec<String> = let_bindings(&hook.stmts)
+                .iter()
+                .map(Ident::to_string)
+                .collect();
+            !uses(&hook.stmts).iter().any(|used| {
+                let used = used.to_string();
+                unreachable.contains(&used) && !own.contains(&used)
+            })
+        })
+        .flat_map(|(_, stmts)| stmts)
+        .collect();
+
+    let timed_body: Expr = if function.sig.asyncness.is_some() {
+        function.block.stmts.push(parse_quote! {
+            let printer_timeout_start = std::time::Instant::now();
+        });
+        parse_quote! {
+            match printer::tokio::time::timeout(
+                std::time::Duration::from_millis(#timeout_millis),
+                async { #(#stmts)* },
+            )
+            .await
+            {
+                Ok(printer_result) => printer_result,
+                Err(_) => {
+                    let printer_elapsed = printer_timeout_start.elapsed();
+                    #(#teardown)*
+                    printer::runtime::timed_out(#name, printer_elapsed)
+                }
+            }
+        }
+    } else {
+        parse_quote! {
+            match printer::runtime::run_with_timeout(
+                #name,
+                std::time::Duration::from_millis(#timeout_millis),
+                move || -> #return_type { #(#stmts)* },
+            ) {
+                Ok(printer_result) => printer_result,
+                Err(printer_elapsed) => {
+                    #(#teardown)*
+                    printer::runtime::timed_out(#name, printer_elapsed)
+                }
+            }
+        }
+    };
+
+    match &function.sig.output {
+        syn::ReturnType::Default => function.block.stmts.push(parse_quote! {
+            #timed_body;
+        }),
+        syn::ReturnType::Type(_, _) => function.block.stmts.push(Stmt::Expr(timed_body, None)),
+    }
+}
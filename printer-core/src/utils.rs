@@ -1,11 +1,34 @@
-use syn::Stmt;
+use proc_macro2::Span;
+use syn::{punctuated::Punctuated, Attribute, Expr, FnArg, ItemFn, Path, Stmt, Token};
+
+use crate::lint::ShadowAllowed;
 
 #[derive(Clone, Debug)]
 pub struct HooksData {
-    pub(crate) before_each: Vec<Vec<Stmt>>,
-    pub(crate) after_each: Vec<Vec<Stmt>>,
-    pub(crate) invariants: Vec<Vec<Stmt>>,
-    pub(crate) before_all: Vec<Stmt>, // potentially other vecs can also look like this
+    pub(crate) before_each: Vec<Hook>,
+    pub(crate) after_each: Vec<Hook>,
+    pub(crate) invariants: Vec<Hook>,
+    pub(crate) before_all: Vec<Hook>,
+    /// Resources locked by the `before_each`, `after_each` and `invariant` hooks.
+    pub(crate) exclusive: Vec<String>,
+    /// Resources locked by the `before_all` hooks.
+    pub(crate) before_all_exclusive: Vec<String>,
+    /// Whether the code of the hooks is surrounded with the events marking the hooks, for the
+    /// runtime reporting and the annotated expansion.
+    pub(crate) events: bool,
+}
+
+/// The statements of a hook function, together with its name.
+#[derive(Clone, Debug)]
+pub struct Hook {
+    pub(crate) name: String,
+    /// The span of the name of the hook function.
+    pub(crate) span: Span,
+    /// What `#[printer::allow(shadow)]` silences in the hook.
+    pub(crate) allowed: ShadowAllowed,
+    /// Whether the hook is inserted only into the tests having the parameters it uses.
+    pub(crate) only: bool,
+    pub(crate) stmts: Vec<Stmt>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -31,12 +54,12 @@ impl PrinterAttributeVariant {
         None
     }
 
-    pub fn populate_hooks_data(&self, hooks_data: &mut HooksData, stmt_vec: Vec<Stmt>) {
+    pub fn populate_hooks_data(&self, hooks_data: &mut HooksData, hook: Hook) {
         match self {
-            PrinterAttributeVariant::BeforeEach => hooks_data.before_each.push(stmt_vec),
-            PrinterAttributeVariant::AfterEach => hooks_data.after_each.push(stmt_vec),
-            PrinterAttributeVariant::Invariant => hooks_data.invariants.push(stmt_vec),
-            PrinterAttributeVariant::BeforeAll => hooks_data.before_all.extend(stmt_vec),
+            PrinterAttributeVariant::BeforeEach => hooks_data.before_each.push(hook),
+            PrinterAttributeVariant::AfterEach => hooks_data.after_each.push(hook),
+            PrinterAttributeVariant::Invariant => hooks_data.invariants.push(hook),
+            PrinterAttributeVariant::BeforeAll => hooks_data.before_all.push(hook),
         }
     }
 }
@@ -47,6 +70,22 @@ pub enum FunctionAttributeVariant {
     External,
 }
 
+impl FunctionAttributeVariant {
+    /// Returns the variant of the hook the function is marked with, if any.
+    pub fn from_attrs(attrs: &[Attribute]) -> Self {
+        attrs
+            .iter()
+            .filter_map(|attr| match &attr.meta {
+                syn::Meta::Path(path) => Some(Self::from(path.segments[0].ident.to_string())),
+                // e.g. `#[before_each(only)]`
+                syn::Meta::List(list) => Some(Self::from(list.path.segments[0].ident.to_string())),
+                syn::Meta::NameValue(_) => None,
+            })
+            .find(|variant| *variant != Self::External)
+            .unwrap_or(Self::External)
+    }
+}
+
 impl From<String> for FunctionAttributeVariant {
     fn from(value: String) -> Self {
         if let Some(variant) = PrinterAttributeVariant::try_from_string(value) {
@@ -56,3 +95,99 @@ impl From<String> for FunctionAttributeVariant {
         Self::External
     }
 }
+
+/// Returns the number of test cases libtest will run for the given function, or `None` if the
+/// function is not a test at all.
+///
+/// Regular tests (`#[test]`, `#[tokio::test]`, `#[ink_e2e::test]`, ...) count as a single case.
+/// `#[rstest]` functions expand to one test per `#[case]` attribute, multiplied by the number of
+/// values of every `#[values(...)]` parameter.
+pub fn count_test_cases(func: &ItemFn) -> Option<usize> {
+    let is_rstest = func
+        .attrs
+        .iter()
+        .any(|attr| last_segment_is(attr.path(), "rstest"));
+    if is_rstest {
+        let cases_num = func
+            .attrs
+            .iter()
+            .filter(|attr| first_segment_is(attr.path(), "case"))
+            .count()
+            .max(1);
+
+        let values_num: usize = func
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(pat_type) => pat_type
+                    .attrs
+                    .iter()
+                    .find(|attr| last_segment_is(attr.path(), "values")),
+                FnArg::Receiver(_) => None,
+            })
+            .map(|attr| {
+                attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+                    .map(|values| values.len())
+                    .unwrap_or(1)
+            })
+            .product();
+
+        return Some(cases_num * values_num);
+    }
+
+    let is_test = func
+        .attrs
+        .iter()
+        .any(|attr| last_segment_is(attr.path(), "test"));
+    is_test.then_some(1)
+}
+
+fn first_segment_is(path: &Path, name: &str) -> bool {
+    path.segments
+        .first()
+        .is_some_and(|segment| segment.ident == name)
+}
+
+fn last_segment_is(path: &Path, name: &str) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == name)
+}
+
+/// Turns the values into a part of an identifier, e.g. `alice()` into `alice` and
+/// `(1, "two")` into `1_two`.
+pub fn name_suffix(values: &[Expr]) -> String {
+    let tokens = values
+        .iter()
+        .map(|value| quote::quote!(#value).to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut suffix = String::new();
+    for ch in tokens.chars() {
+        if ch.is_ascii_alphanumeric() {
+            suffix.push(ch.to_ascii_lowercase());
+        } else if ch == '-' {
+            if !suffix.is_empty() && !suffix.ends_with('_') {
+                suffix.push('_');
+            }
+            suffix.push_str("minus_");
+        } else if !suffix.is_empty() && !suffix.ends_with('_') {
+            suffix.push('_');
+        }
+    }
+
+    let suffix = suffix.trim_end_matches('_');
+    if suffix.is_empty() {
+        "case".to_string()
+    } else {
+        suffix.to_string()
+    }
+}
+
+/// Removes the attribute with the given name from `attrs` and returns it.
+pub fn take_attr(attrs: &mut Vec<Attribute>, name: &str) -> Option<Attribute> {
+    let position = attrs.iter().position(|attr| attr.path().is_ident(name))?;
+    Some(attrs.remove(position))
+}
@@ -1,10 +1,14 @@
 #![doc = include_str!("../README.md")]
 
 use proc_macro_error::proc_macro_error;
-use printer_core::printer_core;
+use printer_core::{printer_core, printer_core_reporting};
 
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn printer(args: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    printer_core(args.into(), input.into()).into()
+    if cfg!(feature = "reporting") {
+        printer_core_reporting(args.into(), input.into()).into()
+    } else {
+        printer_core(args.into(), input.into()).into()
+    }
 }
\ No newline at end of file
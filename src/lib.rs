@@ -1,6 +1,8 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+pub mod runtime;
+
 /// A macro to write test cases that might seem more familiar to mocha users.
 /// It was designed to be used with ink! smart contracts (hence the name - printer), however it works for any test cases.
 /// The whole idea revolves around having more concise code. 
@@ -8,6 +10,8 @@
 /// 
 /// # Example
 /// ```
+/// use printer::printer;
+///
 /// #[cfg(test)]
 /// #[printer]
 /// mod tests {
@@ -42,8 +46,38 @@
 ///     assert!(10 > 3);
 /// }
 /// ```
-
+// the examples show the tests the macro expands to
+#[allow(clippy::useless_attribute, clippy::test_attr_in_doctest)]
 pub use printer_derive::printer;
+
+/// Ends the current test early without failing it and reports it as skipped, e.g. when the
+/// environment required by the test is missing.
+///
+/// Inside of a [`printer`] module, it can be used in the tests as well as in the `before_each` and
+/// `invariant` hooks - the `after_each` hooks are still executed before the test returns. The reason
+/// accepts the same arguments as [`format!`].
+///
+/// # Example
+/// ```ignore
+/// #[before_each]
+/// fn setup() {
+///     if std::env::var("CONTRACTS_NODE").is_err() {
+///         printer::skip!("CONTRACTS_NODE is not set");
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! skip {
+    () => {
+        $crate::skip!("skipped")
+    };
+    ($($reason:tt)+) => {{
+        $crate::runtime::skip(::std::format!($($reason)+));
+        return $crate::runtime::Skipped::skipped();
+    }};
+}
 pub use tokio;
 pub use crossbeam_channel;
-pub use once_cell;
\ No newline at end of file
+pub use once_cell;
+#[cfg(feature = "tracing")]
+pub use tracing;
\ No newline at end of file
@@ -0,0 +1,921 @@
+//! Runtime support for the code generated by the [`printer`](crate::printer) macro. It is not
+//! meant to be used directly.
+
+mod events;
+mod filters;
+mod junit;
+mod logs;
+mod profile;
+mod reporter;
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::future::Future;
+use std::io::Write;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Condvar, Mutex, Once, PoisonError};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use once_cell::sync::OnceCell;
+
+use reporter::{Hook, Report, Status};
+
+/// Names of the resources locked with [`lock_resources`].
+static LOCKED_RESOURCES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static RESOURCE_UNLOCKED: Condvar = Condvar::new();
+
+/// Number of the test threads blocked until other tests finish, across all of the modules.
+static WAITING_TESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// The environment variables ignored without the `reporting` feature.
+const REPORTING_VARS: [&str; 7] = [
+    "PRINTER_GREP",
+    "PRINTER_GREP_INVERT",
+    "PRINTER_BAIL",
+    "PRINTER_REPORTER",
+    "PRINTER_JUNIT",
+    "PRINTER_EVENTS",
+    "PRINTER_PROFILE",
+];
+
+/// `(test, reason)` pairs of the tests skipped with [`skip!`](crate::skip).
+static SKIPPED: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// State shared by all of the tests of a single printer module.
+pub struct Suite {
+    module: &'static str,
+    tests: &'static [(&'static str, usize, bool)],
+    pending: &'static [(&'static str, &'static str)],
+    bail: bool,
+    tests_num: OnceCell<usize>,
+    finished: AtomicUsize,
+    /// The first test of the module that failed.
+    failed: Mutex<Option<&'static str>>,
+    /// The finished tests, for the `spec` reporter.
+    reports: Mutex<Vec<Report>>,
+    started: AtomicBool,
+}
+
+impl Suite {
+    /// Creates the suite of the module at `module` path with the `(test, cases, ignored)` triples of
+    /// its running tests, in the order of their declaration, and the `(test, reason)` pairs of its
+    /// pending tests. With `bail`, the tests are skipped after the first failure, which can be
+    /// enabled for all of the suites with `PRINTER_BAIL=1` as well.
+    pub const fn new(
+        module: &'static str,
+        tests: &'static [(&'static str, usize, bool)],
+        pending: &'static [(&'static str, &'static str)],
+        bail: bool,
+    ) -> Self {
+        Self {
+            module,
+            tests,
+            pending,
+            bail,
+            tests_num: OnceCell::new(),
+            finished: AtomicUsize::new(0),
+            failed: Mutex::new(None),
+            reports: Mutex::new(Vec::new()),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Marks the beginning of the `test`. The test is finished once the returned guard is dropped,
+    /// even if the test panics.
+    pub fn enter(&'static self, test: &'static str) -> TestGuard {
+        check_reporting_env();
+        reporter::test_started();
+        let grepped_out = !filters::matches_grep(&test_path(self.module, test));
+        if !grepped_out {
+            if !self.started.swap(true, Ordering::SeqCst) {
+                events::emit("suite_started", &[("suite", events::string(self.module))]);
+            }
+            events::emit(
+                "test_started",
+                &[
+                    ("suite", events::string(self.module)),
+                    (
+                        "test",
+                        events::string(&test_path(self.module, &test_case_path(self.module, test))),
+                    ),
+                ],
+            );
+        }
+
+        TestGuard {
+            suite: self,
+            test,
+            grepped_out,
+            start: Instant::now(),
+            logs: (!grepped_out).then(|| logs::test_started(&test_case_path(self.module, test))),
+        }
+    }
+
+    /// Like [`Suite::enter`], for a test returning a value checked with [`TestGuard::check`]. The test
+    /// fails if it finishes before the value is checked, which happens only when it returns an error
+    /// early with `?`.
+    pub fn enter_fallible(&'static self, test: &'static str) -> TestGuard {
+        let guard = self.enter(test);
+        if !guard.grepped_out {
+            reporter::test_returns_value();
+        }
+
+        guard
+    }
+
+    fn fail(&self, test: &'static str) {
+        self.failed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(test);
+    }
+
+    fn finish(&self, report: Report) {
+        if reporter::collects_reports() {
+            self.reports
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(report);
+        }
+
+        // the tests filtered out by libtest, or ignored, never finish
+        let tests_num = *self.tests_num.get_or_init(|| {
+            self.tests
+                .iter()
+                .filter(|(test, _, ignored)| {
+                    filters::is_selected(&test_path(self.module, test), *ignored)
+                })
+                .map(|(_, cases_num, _)| cases_num)
+                .sum()
+        });
+        let finished = self.finished.fetch_add(1, Ordering::SeqCst) + 1;
+        if finished == tests_num {
+            self.print_summary();
+        }
+    }
+
+    /// Prints the summary of a module without running tests, i.e. with pending tests only.
+    pub fn finish_pending(&self) {
+        check_reporting_env();
+        self.print_summary();
+    }
+
+    /// Writes directly to stderr, so that the output is not captured by libtest.
+    fn print_summary(&self) {
+        events::emit("suite_finished", &[("suite", events::string(self.module))]);
+        // libtest names the threads of the tests after their path without the crate name
+        let prefix = match self.module.split_once("::") {
+            Some((_, module)) => format!("{}::", module),
+            None => String::new(),
+        };
+        if profile::enabled() {
+            profile::print(self.module, &prefix, self.tests);
+        }
+        if reporter::collects_reports() {
+            let mut reports =
+                std::mem::take(&mut *self.reports.lock().unwrap_or_else(PoisonError::into_inner));
+            reports.sort_by_cached_key(|report| {
+                let position = self
+                    .tests
+                    .iter()
+                    .position(|(test, ..)| *test == report.test);
+                (position, report.path.clone())
+            });
+            if junit::enabled() {
+                junit::write_suite(self.module, &reports, self.pending);
+            }
+            if reporter::enabled() {
+                reporter::print(self.module, &prefix, &reports, self.pending);
+                return;
+            }
+        }
+
+        let skipped: Vec<(String, String)> = SKIPPED
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter_map(|(test, reason)| {
+                let test = test.strip_prefix(&prefix)?;
+                Some((test.to_string(), reason.clone()))
+            })
+            .collect();
+        if self.pending.is_empty() && skipped.is_empty() {
+            return;
+        }
+
+        let mut summary = format!("\npending tests in {}:\n", self.module);
+        for (test, reason) in self.pending {
+            summary.push_str(&format!("  - {}: {}\n", test, reason));
+        }
+        for (test, reason) in skipped {
+            summary.push_str(&format!("  - {}: {}\n", test, reason));
+        }
+
+        let _ = std::io::stderr().lock().write_all(summary.as_bytes());
+    }
+}
+
+/// Guard marking a test of a [`Suite`] as running.
+pub struct TestGuard {
+    suite: &'static Suite,
+    test: &'static str,
+    grepped_out: bool,
+    start: Instant,
+    logs: Option<logs::Capture>,
+}
+
+impl TestGuard {
+    /// Returns true if the title of the test doesn't match the `PRINTER_GREP` patterns, in which
+    /// case the test skips itself without being reported.
+    pub fn is_grepped_out(&self) -> bool {
+        self.grepped_out
+    }
+
+    /// Returns the reason the test should be skipped with in bail mode, if an earlier test of the
+    /// suite failed.
+    pub fn bail_reason(&self) -> Option<String> {
+        let bail_from_env =
+            cfg!(feature = "reporting") && std::env::var("PRINTER_BAIL").as_deref() == Ok("1");
+        if !self.suite.bail && !bail_from_env {
+            return None;
+        }
+
+        let failed = *self
+            .suite
+            .failed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        failed.map(|test| format!("skipped after earlier failure in {}", test))
+    }
+
+    /// Checks the value the test returns.
+    pub fn check<T: Outcome>(&self, output: &T) {
+        reporter::test_returned(output.failure());
+    }
+}
+
+impl Drop for TestGuard {
+    fn drop(&mut self) {
+        let state = reporter::test_finished();
+        // grepped out tests are neither reported nor counted as finished, like the ones filtered
+        // out by libtest
+        if self.grepped_out {
+            return;
+        }
+
+        let failure = if std::thread::panicking() {
+            Some(state.panic.unwrap_or_else(|| "panicked".to_string()))
+        } else {
+            state.returned_failure()
+        };
+        let status = if let Some(message) = failure {
+            self.suite.fail(self.test);
+            Status::Failed {
+                hook: state.hook,
+                message,
+            }
+        } else if let Some(reason) = state.skipped {
+            Status::Skipped(reason)
+        } else {
+            Status::Passed
+        };
+
+        if let (
+            Status::Failed {
+                hook: Some(hook),
+                message,
+            },
+            Some(start),
+        ) = (&status, state.hook_start)
+        {
+            hook_ended(*hook, start.elapsed(), Some(message));
+        }
+        let logs = self.logs.take().map(|logs| {
+            let failed = matches!(status, Status::Failed { .. });
+            let test = test_case_path(self.suite.module, self.test);
+            logs.finish(&test_path(self.suite.module, &test), failed)
+        });
+
+        let report = Report {
+            test: self.test,
+            path: test_case_path(self.suite.module, self.test),
+            status,
+            duration: self.start.elapsed(),
+            logs: logs.unwrap_or_default(),
+        };
+        profile::record(None, report.duration);
+        events::test_finished(self.suite.module, &report);
+        self.suite.finish(report);
+    }
+}
+
+/// Warns once if any of the environment variables of the `reporting` feature is set without the
+/// feature, since they are ignored then. Writes directly to stderr, like the summary of a suite.
+pub fn check_reporting_env() {
+    static CHECKED: Once = Once::new();
+    if cfg!(feature = "reporting") {
+        return;
+    }
+
+    CHECKED.call_once(|| {
+        let set: Vec<&str> = REPORTING_VARS
+            .into_iter()
+            .filter(|var| std::env::var_os(var).is_some())
+            .collect();
+        if !set.is_empty() {
+            let warning = format!(
+                "warning: {} ignored without the `reporting` feature of printer\n",
+                set.join(", ")
+            );
+            let _ = std::io::stderr().lock().write_all(warning.as_bytes());
+        }
+    });
+}
+
+/// Records that the current test was skipped with [`skip!`](crate::skip) and reports it.
+pub fn skip(reason: String) {
+    let test = std::thread::current()
+        .name()
+        .unwrap_or("<unnamed>")
+        .to_string();
+    reporter::test_skipped(&reason);
+    if !reporter::enabled() {
+        let _ = std::io::stderr()
+            .lock()
+            .write_all(format!("test {} skipped: {}\n", test, reason).as_bytes());
+    }
+
+    let mut skipped = SKIPPED.lock().unwrap_or_else(PoisonError::into_inner);
+    // properties can skip many of their cases, the test is reported once
+    if !skipped
+        .iter()
+        .any(|(skipped_test, _)| *skipped_test == test)
+    {
+        skipped.push((test, reason));
+    }
+}
+
+/// Marks the beginning of the hook function `name` of the `kind`, e.g. `before each`, in the
+/// current test. Failures of the test are attributed to the hook until it finishes.
+pub fn hook_started(kind: &'static str, name: &'static str) {
+    let hook = Hook { kind, name };
+    reporter::hook_started(hook);
+    logs::hook_started(kind, name);
+    events::emit(
+        "hook_started",
+        &[
+            ("test", events::string(&events::current_test())),
+            ("hook", events::string(kind)),
+            ("hook_name", events::string(name)),
+        ],
+    );
+}
+
+/// Marks the end of the hook that started last in the current test.
+pub fn hook_finished() {
+    logs::hook_finished();
+    if let Some((hook, duration)) = reporter::hook_finished() {
+        hook_ended(hook, duration, None);
+    }
+}
+
+/// Records the end of the `hook` running on the current thread, which failed with `failure`.
+fn hook_ended(hook: Hook, duration: Duration, failure: Option<&str>) {
+    profile::record(Some(hook), duration);
+    events::hook_finished(hook, duration, failure);
+}
+
+/// Value returned from a test that was skipped with [`skip!`](crate::skip), making it pass.
+pub trait Skipped {
+    /// Returns the passing value.
+    fn skipped() -> Self;
+}
+
+impl Skipped for () {
+    fn skipped() -> Self {}
+}
+
+impl<E> Skipped for Result<(), E> {
+    fn skipped() -> Self {
+        Ok(())
+    }
+}
+
+impl Skipped for bool {
+    fn skipped() -> Self {
+        true
+    }
+}
+
+/// Runs the body of a sync test, with its hooks, on a separate thread and returns its result. Once
+/// the body runs longer than the `timeout`, the elapsed time is returned instead, while the thread
+/// is left running in the background, since it can't be stopped. Panics of the body are propagated
+/// to the caller.
+pub fn run_with_timeout<T: Send + 'static>(
+    test: &str,
+    timeout: Duration,
+    body: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, Duration> {
+    let start = Instant::now();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let context = TestContext::take();
+    // the thread is named after the test, just like the one libtest runs the test on
+    let mut builder = std::thread::Builder::new();
+    if let Some(name) = std::thread::current().name() {
+        builder = builder.name(name.to_string());
+    }
+    builder
+        .spawn(move || {
+            context.restore();
+            let result = std::panic::catch_unwind(AssertUnwindSafe(body));
+            let _ = sender.send((result, TestContext::take()));
+        })
+        .expect("failed to spawn the thread of the test");
+
+    match receiver.recv_timeout(timeout) {
+        Ok((result, context)) => {
+            context.restore();
+            Ok(result.unwrap_or_else(|payload| std::panic::resume_unwind(payload)))
+        }
+        Err(RecvTimeoutError::Timeout) => Err(start.elapsed()),
+        Err(RecvTimeoutError::Disconnected) => {
+            panic!("the thread of test `{}` stopped without finishing", test)
+        }
+    }
+}
+
+/// Fails the test that ran longer than its timeout.
+pub fn timed_out(test: &str, elapsed: Duration) -> ! {
+    panic!("test `{}` timed out after {:?}", test, elapsed)
+}
+
+/// What the test running on the current thread recorded so far, moved along with the test when it
+/// continues on another thread.
+struct TestContext {
+    state: reporter::State,
+    logs: logs::Detached,
+}
+
+impl TestContext {
+    fn take() -> Self {
+        Self {
+            state: reporter::take_state(),
+            logs: logs::detach(),
+        }
+    }
+
+    fn restore(self) {
+        reporter::restore_state(self.state);
+        logs::attach(self.logs);
+    }
+}
+
+/// Attempts of a test with retries.
+pub struct Attempts {
+    test: &'static str,
+    retries: usize,
+    failures: Vec<String>,
+}
+
+impl Attempts {
+    /// Creates the attempts of the `test` that is retried up to `retries` times.
+    pub fn new(test: &'static str, retries: usize) -> Self {
+        Self {
+            test,
+            retries,
+            failures: vec![],
+        }
+    }
+
+    /// Checks the result of the last attempt, logging it if it failed. Returns the output of the
+    /// test once it should not be retried anymore, i.e. when the attempt passed. Panics with the
+    /// errors of all of the attempts if the last one failed.
+    pub fn check<T: Outcome>(&mut self, attempt: std::thread::Result<T>) -> Option<T> {
+        let failure = match &attempt {
+            Ok(output) => output.failure(),
+            Err(payload) => Some(panic_message(payload.as_ref())),
+        };
+        let attempts_num = self.retries + 1;
+
+        let Some(failure) = failure else {
+            if !self.failures.is_empty() {
+                let _ = std::io::stderr().lock().write_all(
+                    format!(
+                        "test `{}` passed on attempt {} of {}\n",
+                        self.test,
+                        self.failures.len() + 1,
+                        attempts_num
+                    )
+                    .as_bytes(),
+                );
+            }
+            return attempt.ok();
+        };
+
+        self.failures.push(failure);
+        let _ = std::io::stderr().lock().write_all(
+            format!(
+                "test `{}` failed on attempt {} of {}: {}\n",
+                self.test,
+                self.failures.len(),
+                attempts_num,
+                self.failures.last().unwrap()
+            )
+            .as_bytes(),
+        );
+
+        if self.failures.len() < attempts_num {
+            return None;
+        }
+
+        let mut message = format!("test `{}` failed all {} attempts:", self.test, attempts_num);
+        for (idx, failure) in self.failures.iter().enumerate() {
+            message.push_str(&format!("\n  attempt {}: {}", idx + 1, failure));
+        }
+        panic!("{}", message)
+    }
+}
+
+/// Output of a test that tells whether it failed.
+pub trait Outcome {
+    /// Returns the error of the failed test.
+    fn failure(&self) -> Option<String>;
+}
+
+impl Outcome for () {
+    fn failure(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<T, E: Debug> Outcome for Result<T, E> {
+    fn failure(&self) -> Option<String> {
+        self.as_ref().err().map(|e| format!("{:?}", e))
+    }
+}
+
+/// Awaits the future, catching its panics like [`std::panic::catch_unwind`].
+pub async fn catch_unwind<F: Future>(future: F) -> std::thread::Result<F::Output> {
+    let mut future = std::pin::pin!(future);
+    std::future::poll_fn(|cx| {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    })
+    .await
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panicked with a non-string payload".to_string()
+    }
+}
+
+/// Times a test with a slow threshold, separately from its hooks.
+pub struct SlowTimer {
+    test: &'static str,
+    threshold: Duration,
+    start: Instant,
+    body_start: Option<Instant>,
+    body_end: Option<Instant>,
+}
+
+impl SlowTimer {
+    /// Starts timing the `test`, which is slow once its body takes longer than `threshold`.
+    pub fn start(test: &'static str, threshold: Duration) -> Self {
+        Self {
+            test,
+            threshold,
+            start: Instant::now(),
+            body_start: None,
+            body_end: None,
+        }
+    }
+
+    /// Marks the end of the before hooks and the beginning of the body.
+    pub fn body_started(&mut self) {
+        self.body_start = Some(Instant::now());
+    }
+
+    /// Marks the end of the body and the beginning of the after hooks.
+    pub fn body_finished(&mut self) {
+        self.body_end = Some(Instant::now());
+    }
+
+    /// Reports the test if its body was slower than the threshold. The test fails if the
+    /// `PRINTER_FAIL_SLOW` environment variable is set to `1`.
+    pub fn finish(&self) {
+        let end = Instant::now();
+        let (Some(body_start), Some(body_end)) = (self.body_start, self.body_end) else {
+            return;
+        };
+        let body = body_end - body_start;
+        if body <= self.threshold {
+            return;
+        }
+
+        let message = format!(
+            "test `{}` is slow: its body took {} (threshold: {}, before hooks: {}, after hooks: {}, total: {})",
+            self.test,
+            format_duration(body),
+            format_duration(self.threshold),
+            format_duration(body_start - self.start),
+            format_duration(end - body_end),
+            format_duration(end - self.start),
+        );
+        if std::env::var("PRINTER_FAIL_SLOW").as_deref() == Ok("1") {
+            panic!("{}", message);
+        }
+
+        let _ = std::io::stderr()
+            .lock()
+            .write_all(format!("warning: {}\n", message).as_bytes());
+    }
+}
+
+/// Returns the name of the test binary, without the hash cargo adds to it.
+fn binary_name() -> String {
+    let name = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    match name.rsplit_once('-') {
+        Some((name, hash)) if hash.chars().all(|c| c.is_ascii_hexdigit()) => name.to_string(),
+        _ => name,
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+/// Semaphore limiting the number of the tests of a module running at the same time.
+pub struct Semaphore {
+    permits: usize,
+    used: Mutex<usize>,
+    released: Condvar,
+}
+
+impl Semaphore {
+    /// Creates the semaphore allowing `permits` tests to run at the same time.
+    pub const fn new(permits: usize) -> Self {
+        Self {
+            permits,
+            used: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks the test until it's allowed to run. The permit is released once the returned guard
+    /// is dropped, even if the test panics.
+    pub fn acquire(&'static self) -> Permit {
+        let mut used = self.used.lock().unwrap_or_else(PoisonError::into_inner);
+        while *used >= self.permits {
+            used = self
+                .released
+                .wait(used)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        *used += 1;
+
+        Permit { semaphore: self }
+    }
+}
+
+/// Guard holding a permit of a [`Semaphore`].
+pub struct Permit {
+    semaphore: &'static Semaphore,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut used = self
+            .semaphore
+            .used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *used -= 1;
+        self.semaphore.released.notify_one();
+    }
+}
+
+/// Locks the named resources shared by the tests of the whole test binary, blocking until none of
+/// the other tests holds any of them. The resources are locked one by one in a sorted order, so that
+/// tests locking several resources can't deadlock. They are unlocked once the returned guard is
+/// dropped, even if the test panics.
+pub fn lock_resources(resources: &[&'static str]) -> ResourcesGuard {
+    let mut resources = resources.to_vec();
+    resources.sort_unstable();
+    resources.dedup();
+
+    let mut locked = LOCKED_RESOURCES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    for resource in &resources {
+        while locked.contains(resource) {
+            locked = RESOURCE_UNLOCKED
+                .wait(locked)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        locked.push(resource);
+    }
+
+    ResourcesGuard { resources }
+}
+
+/// Guard holding the resources locked with [`lock_resources`].
+pub struct ResourcesGuard {
+    resources: Vec<&'static str>,
+}
+
+impl Drop for ResourcesGuard {
+    fn drop(&mut self) {
+        let mut locked = LOCKED_RESOURCES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        locked.retain(|resource| !self.resources.contains(resource));
+        RESOURCE_UNLOCKED.notify_all();
+    }
+}
+
+/// `(test, cases, ignored, dependencies)` of a test taking part in [`Completions`]. The test is not
+/// run at all if it has no cases. The dependencies are the indices of the tests it waits for, with
+/// a flag telling whether they are required to run.
+pub type CompletionTest = (&'static str, usize, bool, &'static [(usize, bool)]);
+
+/// Completions of the tests of a module, which let the tests wait for each other.
+pub struct Completions {
+    module: &'static str,
+    tests: &'static [CompletionTest],
+    states: OnceCell<Vec<CompletionState>>,
+}
+
+struct CompletionState {
+    /// Number of the cases done waiting for their dependencies.
+    started: AtomicUsize,
+    finished: AtomicUsize,
+    failed: AtomicBool,
+    // the outcome of the finished test, every receiver sends it back for the other ones
+    sender: Sender<bool>,
+    receiver: Receiver<bool>,
+}
+
+impl Completions {
+    /// Creates the completions of the `tests` of the module at `module` path.
+    pub const fn new(module: &'static str, tests: &'static [CompletionTest]) -> Self {
+        Self {
+            module,
+            tests,
+            states: OnceCell::new(),
+        }
+    }
+
+    /// Blocks the test at the `test` index until its dependencies finish, returns the reason to skip
+    /// it with if any of them failed or is not run. Panics instead of blocking if a dependency
+    /// didn't start yet and no other test thread is free to run it, e.g. with `--test-threads=1`.
+    pub fn wait(&'static self, test: usize) -> Result<Completion, String> {
+        // the dependents of the test are skipped unless it is done waiting, even if it panics
+        let mut completion = Completion {
+            completions: self,
+            test,
+            failed: true,
+        };
+        self.wait_for_dependencies(test)?;
+        completion.failed = false;
+        self.states()[test].started.fetch_add(1, Ordering::SeqCst);
+
+        Ok(completion)
+    }
+
+    fn wait_for_dependencies(&self, test: usize) -> Result<(), String> {
+        for &(dependency, required) in self.tests[test].3 {
+            let (name, cases_num, ignored, _) = self.tests[dependency];
+            if cases_num == 0 {
+                return Err(format!("skipped because `{}` is not run", name));
+            }
+            if !filters::is_selected(&test_path(self.module, name), ignored) {
+                if required {
+                    return Err(format!("skipped because `{}` was filtered out", name));
+                }
+                self.wait_for_dependencies(dependency)?;
+                continue;
+            }
+
+            if !self.wait_for(test, dependency) {
+                return Err(format!("skipped because `{}` did not pass", name));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the `dependency` of the `test` passed, once it finishes.
+    fn wait_for(&self, test: usize, dependency: usize) -> bool {
+        let state = &self.states()[dependency];
+        if let Ok(passed) = state.receiver.try_recv() {
+            let _ = state.sender.send(passed);
+            return passed;
+        }
+
+        // waiting for a started test is fine, but an unstarted one needs a free thread to run on
+        let (name, cases_num, ..) = self.tests[dependency];
+        let started = state.started.load(Ordering::SeqCst) == cases_num;
+        let waiting = WAITING_TESTS.fetch_add(1, Ordering::SeqCst) + 1;
+        if !started && waiting >= filters::test_threads() {
+            WAITING_TESTS.fetch_sub(1, Ordering::SeqCst);
+            panic!(
+                "test `{}` has to wait for `{}`, which didn't start yet, but no other test thread \
+                 is free to run it; run the tests with more `--test-threads`, or name them in the \
+                 order they run in, since libtest starts the tests in the alphabetical order",
+                self.tests[test].0, name
+            );
+        }
+
+        let passed = state
+            .receiver
+            .recv()
+            .expect("the channel of the test is never closed");
+        WAITING_TESTS.fetch_sub(1, Ordering::SeqCst);
+        let _ = state.sender.send(passed);
+        passed
+    }
+
+    fn finish(&self, test: usize, passed: bool) {
+        let state = &self.states()[test];
+        if !passed {
+            state.failed.store(true, Ordering::SeqCst);
+        }
+        // rstest functions finish once all of their cases do
+        if state.finished.fetch_add(1, Ordering::SeqCst) + 1 == self.tests[test].1 {
+            let _ = state.sender.send(!state.failed.load(Ordering::SeqCst));
+        }
+    }
+
+    fn states(&self) -> &[CompletionState] {
+        self.states.get_or_init(|| {
+            self.tests
+                .iter()
+                .map(|_| {
+                    let (sender, receiver) = unbounded();
+                    CompletionState {
+                        started: AtomicUsize::new(0),
+                        finished: AtomicUsize::new(0),
+                        failed: AtomicBool::new(false),
+                        sender,
+                        receiver,
+                    }
+                })
+                .collect()
+        })
+    }
+}
+
+/// Returns the path libtest identifies the `test` of the `module` with, i.e. without the crate name.
+fn test_path(module: &str, test: &str) -> String {
+    match module.split_once("::") {
+        Some((_, module)) => format!("{}::{}", module, test),
+        None => test.to_string(),
+    }
+}
+
+/// Returns the path of the case of the `test` running on the current thread in the `module`, e.g.
+/// `it_works::case_1` for rstest tests.
+fn test_case_path(module: &str, test: &str) -> String {
+    let prefix = test_path(module, "");
+    std::thread::current()
+        .name()
+        .and_then(|thread| thread.strip_prefix(&prefix))
+        .filter(|path| path.starts_with(test))
+        .unwrap_or(test)
+        .to_string()
+}
+
+/// Guard marking a test of [`Completions`] as running. The test is finished once the guard is
+/// dropped, it fails if it panics or returns a failure, see [`TestGuard::check`].
+pub struct Completion {
+    completions: &'static Completions,
+    test: usize,
+    failed: bool,
+}
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        // the tests depending on a skipped test are skipped as well
+        let passed = !self.failed && !std::thread::panicking() && reporter::passed();
+        self.completions.finish(self.test, passed);
+    }
+}
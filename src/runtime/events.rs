@@ -0,0 +1,219 @@
+//! The newline-delimited JSON stream of the lifecycle events of the suites, tests and hooks,
+//! written to the path of `PRINTER_EVENTS`.
+
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+
+use super::reporter::{Hook, Report, Status};
+use super::{binary_name, test_path};
+
+/// The file of the events, with the `reporting` feature. `{binary}` in the path is replaced with the
+/// name of the test binary.
+static FILE: Lazy<Option<Mutex<File>>> = Lazy::new(|| {
+    let path = std::env::var("PRINTER_EVENTS").ok()?;
+    if path.is_empty() || !cfg!(feature = "reporting") {
+        return None;
+    }
+
+    let path = path.replace("{binary}", &binary_name());
+    let file = std::path::Path::new(&path)
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| File::create(&path));
+    match file {
+        Ok(file) => Some(Mutex::new(file)),
+        Err(e) => {
+            let _ = std::io::stderr().lock().write_all(
+                format!(
+                    "warning: failed to create the events file {}: {}\n",
+                    path, e
+                )
+                .as_bytes(),
+            );
+            None
+        }
+    }
+});
+
+/// Returns true if the events are written.
+pub(super) fn enabled() -> bool {
+    FILE.is_some()
+}
+
+/// Writes the `event` with the given fields, which are JSON values, and the current time in
+/// seconds since the Unix epoch. Every event is written at once, so the events of the tests running
+/// in parallel are not interleaved.
+pub(super) fn emit(event: &str, fields: &[(&str, String)]) {
+    let Some(file) = &*FILE else {
+        return;
+    };
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let line = line(event, time, fields);
+    let _ = file
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .write_all(line.as_bytes());
+}
+
+/// Returns the line of the `event` which happened at `time` since the Unix epoch.
+fn line(event: &str, time: Duration, fields: &[(&str, String)]) -> String {
+    let mut line = format!("{{\"event\":{},\"time\":{}", string(event), seconds(time));
+    for (name, value) in fields {
+        line.push_str(&format!(",{}:{}", string(name), value));
+    }
+    line.push_str("}\n");
+    line
+}
+
+/// Writes the end of the test of the suite at `module` path.
+pub(super) fn test_finished(module: &str, report: &Report) {
+    emit("test_finished", &test_fields(module, report));
+}
+
+fn test_fields(module: &str, report: &Report) -> Vec<(&'static str, String)> {
+    let mut fields = vec![
+        ("suite", string(module)),
+        ("test", string(&test_path(module, &report.path))),
+        ("duration", seconds(report.duration)),
+    ];
+    match &report.status {
+        Status::Passed => fields.push(("outcome", string("passed"))),
+        Status::Failed { hook, message } => {
+            fields.push(("outcome", string("failed")));
+            if let Some(hook) = hook {
+                fields.push(("hook", string(hook.kind)));
+                fields.push(("hook_name", string(hook.name)));
+            }
+            fields.push(("failure", string(message)));
+        }
+        Status::Skipped(reason) => {
+            fields.push(("outcome", string("skipped")));
+            fields.push(("reason", string(reason)));
+        }
+    }
+
+    fields
+}
+
+/// Writes the end of the `hook` running on the current thread, which failed with `failure`.
+pub(super) fn hook_finished(hook: Hook, duration: Duration, failure: Option<&str>) {
+    let outcome = if failure.is_some() {
+        "failed"
+    } else {
+        "passed"
+    };
+    let mut fields = vec![
+        ("test", string(&current_test())),
+        ("hook", string(hook.kind)),
+        ("hook_name", string(hook.name)),
+        ("duration", seconds(duration)),
+        ("outcome", string(outcome)),
+    ];
+    if let Some(failure) = failure {
+        fields.push(("failure", string(failure)));
+    }
+
+    emit("hook_finished", &fields);
+}
+
+/// Returns the path libtest shows for the test running on the current thread.
+pub(super) fn current_test() -> String {
+    std::thread::current()
+        .name()
+        .unwrap_or("<unnamed>")
+        .to_string()
+}
+
+/// Returns the JSON string of `text`.
+pub(super) fn string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.6}", duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(status: Status) -> Report {
+        Report {
+            test: "it_works",
+            path: "it_works".to_string(),
+            status,
+            duration: Duration::from_millis(1500),
+            logs: String::new(),
+        }
+    }
+
+    fn test_line(status: Status) -> String {
+        line(
+            "test_finished",
+            Duration::from_secs(1700000000),
+            &test_fields("flipper::tests", &report(status)),
+        )
+    }
+
+    #[test]
+    fn writes_the_passed_tests() {
+        assert_eq!(
+            test_line(Status::Passed),
+            "{\"event\":\"test_finished\",\"time\":1700000000.000000,\"suite\":\"flipper::tests\",\"test\":\"tests::it_works\",\"duration\":1.500000,\"outcome\":\"passed\"}\n"
+        );
+    }
+
+    #[test]
+    fn writes_the_failed_tests_with_their_hook() {
+        let status = Status::Failed {
+            hook: Some(Hook {
+                kind: "after each",
+                name: "teardown",
+            }),
+            message: "assertion failed: `left == right`\n  left: \"a\"".to_string(),
+        };
+        assert_eq!(
+            test_line(status),
+            "{\"event\":\"test_finished\",\"time\":1700000000.000000,\"suite\":\"flipper::tests\",\"test\":\"tests::it_works\",\"duration\":1.500000,\"outcome\":\"failed\",\"hook\":\"after each\",\"hook_name\":\"teardown\",\"failure\":\"assertion failed: `left == right`\\n  left: \\\"a\\\"\"}\n"
+        );
+    }
+
+    #[test]
+    fn writes_the_skipped_tests() {
+        assert_eq!(
+            test_line(Status::Skipped("no node".to_string())),
+            "{\"event\":\"test_finished\",\"time\":1700000000.000000,\"suite\":\"flipper::tests\",\"test\":\"tests::it_works\",\"duration\":1.500000,\"outcome\":\"skipped\",\"reason\":\"no node\"}\n"
+        );
+    }
+
+    #[test]
+    fn escapes_the_strings() {
+        assert_eq!(string("a \"b\" \\ c"), "\"a \\\"b\\\" \\\\ c\"");
+        assert_eq!(string("\n\r\t"), "\"\\n\\r\\t\"");
+        assert_eq!(
+            string("\u{1b}[0m\0 ünïcode"),
+            "\"\\u001b[0m\\u0000 ünïcode\""
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Parsing of the arguments libtest filters the tests with, and of the `PRINTER_GREP` patterns
+//! matched against the titles of the tests.
+
+use once_cell::sync::Lazy;
+
+/// Filters passed to the test binary, e.g. `cargo test -- upgrade --skip migrate`.
+struct Filters {
+    filters: Vec<String>,
+    skip: Vec<String>,
+    exact: bool,
+    ignored: bool,
+    include_ignored: bool,
+    test_threads: Option<usize>,
+}
+
+static FILTERS: Lazy<Filters> = Lazy::new(|| Filters::parse(std::env::args().skip(1)));
+
+/// Lowercased `PRINTER_GREP` and `PRINTER_GREP_INVERT` patterns, with the `reporting` feature. Empty
+/// ones are ignored.
+static GREP: Lazy<(Option<String>, Option<String>)> = Lazy::new(|| {
+    (
+        grep_pattern("PRINTER_GREP"),
+        grep_pattern("PRINTER_GREP_INVERT"),
+    )
+});
+
+/// libtest options that take a value, the value is not a filter.
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "--test-threads",
+    "--skip",
+    "--format",
+    "--logfile",
+    "--color",
+    "--shuffle-seed",
+    "-Z",
+];
+
+impl Filters {
+    fn parse(args: impl Iterator<Item = String>) -> Self {
+        let mut filters = Self {
+            filters: vec![],
+            skip: vec![],
+            exact: false,
+            ignored: false,
+            include_ignored: false,
+            test_threads: None,
+        };
+
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            if let Some(option) = OPTIONS_WITH_VALUE.iter().find(|option| **option == arg) {
+                let value = args.next();
+                match *option {
+                    "--skip" => filters.skip.extend(value),
+                    "--test-threads" => {
+                        filters.test_threads = value.and_then(|value| value.parse().ok())
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match arg.as_str() {
+                "--exact" => filters.exact = true,
+                "--ignored" => filters.ignored = true,
+                "--include-ignored" => filters.include_ignored = true,
+                _ if arg.starts_with("--skip=") => {
+                    filters.skip.push(arg["--skip=".len()..].to_string())
+                }
+                _ if arg.starts_with("--test-threads=") => {
+                    filters.test_threads = arg["--test-threads=".len()..].parse().ok()
+                }
+                _ if arg.starts_with('-') => {}
+                _ => filters.filters.push(arg),
+            }
+        }
+
+        filters
+    }
+
+    /// Returns true if libtest runs the test with the given path, which is `ignored` or not.
+    fn selects(&self, test: &str, ignored: bool) -> bool {
+        if ignored && !self.ignored && !self.include_ignored {
+            return false;
+        }
+        if !ignored && self.ignored {
+            return false;
+        }
+
+        (self.filters.is_empty() || self.filters.iter().any(|filter| self.matches(filter, test)))
+            && !self.skip.iter().any(|skip| self.matches(skip, test))
+    }
+
+    fn matches(&self, filter: &str, test: &str) -> bool {
+        if self.exact {
+            test == filter
+        } else {
+            test.contains(filter)
+        }
+    }
+}
+
+/// Returns true if libtest runs the test with the given path (without the crate name) and it is not
+/// skipped by the `PRINTER_GREP` patterns, taking the filters passed to the test binary into
+/// account.
+pub fn is_selected(test: &str, ignored: bool) -> bool {
+    matches_grep(test) && FILTERS.selects(test, ignored)
+}
+
+/// Returns the number of the threads libtest runs the tests on, set with `--test-threads` or
+/// `RUST_TEST_THREADS`, and defaulting to the available parallelism.
+pub fn test_threads() -> usize {
+    FILTERS
+        .test_threads
+        .or_else(|| std::env::var("RUST_TEST_THREADS").ok()?.parse().ok())
+        .or_else(|| std::thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1)
+}
+
+/// Returns true if the title of the test with the given path (without the crate name) contains the
+/// `PRINTER_GREP` pattern and doesn't contain the `PRINTER_GREP_INVERT` one, ignoring case.
+pub fn matches_grep(test: &str) -> bool {
+    let (grep, invert) = &*GREP;
+    matches_patterns(test, grep.as_deref(), invert.as_deref())
+}
+
+fn matches_patterns(test: &str, grep: Option<&str>, invert: Option<&str>) -> bool {
+    let title = title(test);
+
+    grep.is_none_or(|grep| title.contains(grep))
+        && !invert.is_some_and(|invert| title.contains(invert))
+}
+
+/// Returns the human-readable title of the test, e.g. `flipper tests flip works` for
+/// `flipper::tests::flip_works`.
+fn title(test: &str) -> String {
+    test.split("::")
+        .map(|segment| segment.replace('_', " "))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn grep_pattern(var: &str) -> Option<String> {
+    if !cfg!(feature = "reporting") {
+        return None;
+    }
+
+    let pattern = std::env::var(var).ok()?.trim().to_lowercase();
+    (!pattern.is_empty()).then_some(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Filters {
+        Filters::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn filters_by_substring() {
+        let filters = parse(&["upgrade"]);
+
+        assert!(filters.selects("tests::upgrade_works", false));
+        assert!(filters.selects("tests::upgrade", false));
+        assert!(!filters.selects("tests::deploy_works", false));
+    }
+
+    #[test]
+    fn filters_exactly() {
+        let filters = parse(&["tests::upgrade", "--exact"]);
+
+        assert!(filters.selects("tests::upgrade", false));
+        assert!(!filters.selects("tests::upgrade_works", false));
+    }
+
+    #[test]
+    fn skips() {
+        let filters = parse(&["--skip", "migrate", "--skip=deploy", "tests::"]);
+
+        assert_eq!(filters.filters, ["tests::"]);
+        assert!(filters.selects("tests::upgrade_works", false));
+        assert!(!filters.selects("tests::migrate_works", false));
+        assert!(!filters.selects("tests::deploy_works", false));
+    }
+
+    #[test]
+    fn skips_exactly() {
+        let filters = parse(&["--skip", "tests::upgrade", "--exact"]);
+
+        assert!(!filters.selects("tests::upgrade", false));
+        assert!(filters.selects("tests::upgrade_works", false));
+    }
+
+    #[test]
+    fn ignored_tests() {
+        let filters = parse(&[]);
+        assert!(filters.selects("tests::upgrade_works", false));
+        assert!(!filters.selects("tests::upgrade_works", true));
+
+        let filters = parse(&["--ignored"]);
+        assert!(!filters.selects("tests::upgrade_works", false));
+        assert!(filters.selects("tests::upgrade_works", true));
+
+        let filters = parse(&["--include-ignored"]);
+        assert!(filters.selects("tests::upgrade_works", false));
+        assert!(filters.selects("tests::upgrade_works", true));
+    }
+
+    #[test]
+    fn option_values_are_not_filters() {
+        let filters = parse(&[
+            "--test-threads",
+            "2",
+            "--format",
+            "pretty",
+            "-Z",
+            "unstable-options",
+        ]);
+
+        assert!(filters.filters.is_empty());
+        assert_eq!(filters.test_threads, Some(2));
+        assert_eq!(parse(&["--test-threads=4"]).test_threads, Some(4));
+    }
+
+    #[test]
+    fn titles() {
+        assert_eq!(
+            title("flipper::tests::flip_works"),
+            "flipper tests flip works"
+        );
+        assert_eq!(title("tests::Flip_Works"), "tests flip works");
+    }
+
+    #[test]
+    fn greps_titles() {
+        assert!(matches_patterns("flipper::flip_works", None, None));
+        assert!(matches_patterns(
+            "flipper::flip_works",
+            Some("flipper flip"),
+            None
+        ));
+        assert!(!matches_patterns(
+            "flipper::flip_works",
+            Some("flip_works"),
+            None
+        ));
+        assert!(!matches_patterns(
+            "flipper::flip_works",
+            Some("flipper"),
+            Some("works")
+        ));
+        assert!(matches_patterns(
+            "flipper::flip_twice",
+            Some("flipper"),
+            Some("works")
+        ));
+    }
+}
@@ -0,0 +1,270 @@
+//! The JUnit XML report, written to the path of `PRINTER_JUNIT`. Every module is a `<testsuite>` of
+//! its own, so the reports of several test binaries can be merged by putting their `<testsuite>`
+//! elements under a single `<testsuites>` element.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+
+use super::binary_name;
+use super::reporter::{Report, Status};
+
+/// The path of the report, with the `reporting` feature. `{binary}` is replaced with the name of the
+/// test binary.
+static PATH: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    let path = std::env::var("PRINTER_JUNIT").ok()?;
+    if path.is_empty() || !cfg!(feature = "reporting") {
+        return None;
+    }
+
+    Some(PathBuf::from(path.replace("{binary}", &binary_name())))
+});
+
+/// The `<testsuite>` elements of the finished modules.
+static SUITES: Mutex<Vec<TestSuite>> = Mutex::new(Vec::new());
+
+struct TestSuite {
+    tests: usize,
+    failures: usize,
+    skipped: usize,
+    time: Duration,
+    xml: String,
+}
+
+/// Returns true if the JUnit report is enabled.
+pub(super) fn enabled() -> bool {
+    PATH.is_some()
+}
+
+/// Adds the finished module at `module` path to the report and writes it. The report is rewritten
+/// after every module, so it holds all of the tests once the last module finishes.
+pub(super) fn write_suite(
+    module: &str,
+    reports: &[Report],
+    pending: &[(&'static str, &'static str)],
+) {
+    let Some(path) = &*PATH else {
+        return;
+    };
+
+    let suite = render_suite(module, reports, pending);
+    let mut suites = SUITES.lock().unwrap_or_else(PoisonError::into_inner);
+    suites.push(suite);
+    if let Err(e) = write_report(path, &suites) {
+        let _ = std::io::stderr().lock().write_all(
+            format!(
+                "warning: failed to write the JUnit report to {}: {}\n",
+                path.display(),
+                e
+            )
+            .as_bytes(),
+        );
+    }
+}
+
+/// Renders the `<testsuite>` of the module at `module` path. The captured `tracing` logs of a test
+/// are its `<system-out>`, which is left out without them.
+fn render_suite(
+    module: &str,
+    reports: &[Report],
+    pending: &[(&'static str, &'static str)],
+) -> TestSuite {
+    let mut suite = TestSuite {
+        tests: reports.len() + pending.len(),
+        failures: 0,
+        skipped: pending.len(),
+        time: Duration::ZERO,
+        xml: String::new(),
+    };
+    for report in reports {
+        suite.time += report.duration;
+        suite.xml.push_str(&format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(&report.path),
+            escape(module),
+            report.duration.as_secs_f64()
+        ));
+        match &report.status {
+            Status::Passed => suite.xml.push_str(">\n"),
+            Status::Failed { hook, message } => {
+                suite.failures += 1;
+                let message = match hook {
+                    Some(hook) => format!("{} failed: {}", hook, message),
+                    None => message.clone(),
+                };
+                suite.xml.push_str(&format!(
+                    ">\n      <failure message=\"{}\">{}</failure>\n",
+                    escape(message.lines().next().unwrap_or_default()),
+                    escape(&message)
+                ));
+            }
+            Status::Skipped(reason) => {
+                suite.skipped += 1;
+                suite.xml.push_str(&format!(
+                    ">\n      <skipped message=\"{}\"/>\n",
+                    escape(reason)
+                ));
+            }
+        }
+        if !report.logs.is_empty() {
+            suite.xml.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                escape(&report.logs)
+            ));
+        }
+        suite.xml.push_str("    </testcase>\n");
+    }
+    for (test, reason) in pending {
+        suite.xml.push_str(&format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"0.000\">\n      <skipped message=\"pending: {}\"/>\n    </testcase>\n",
+            escape(test),
+            escape(module),
+            escape(reason)
+        ));
+    }
+    suite.xml = format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\">\n{}  </testsuite>\n",
+        escape(module),
+        suite.tests,
+        suite.failures,
+        suite.skipped,
+        suite.time.as_secs_f64(),
+        suite.xml
+    );
+
+    suite
+}
+
+fn write_report(path: &Path, suites: &[TestSuite]) -> std::io::Result<()> {
+    let mut report = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\">\n",
+        escape(&binary_name()),
+        suites.iter().map(|suite| suite.tests).sum::<usize>(),
+        suites.iter().map(|suite| suite.failures).sum::<usize>(),
+        suites.iter().map(|suite| suite.skipped).sum::<usize>(),
+        suites
+            .iter()
+            .map(|suite| suite.time)
+            .sum::<Duration>()
+            .as_secs_f64(),
+    );
+    for suite in suites {
+        report.push_str(&suite.xml);
+    }
+    report.push_str("</testsuites>\n");
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, report)
+}
+
+/// Escapes the text for the XML attributes and elements. The control characters XML doesn't allow,
+/// e.g. the escape sequences of colored output, are replaced with U+FFFD.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() && c < '\u{80}' => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::reporter::Hook;
+    use super::*;
+
+    fn report(path: &str, status: Status, logs: &str) -> Report {
+        Report {
+            test: "",
+            path: path.to_string(),
+            status,
+            duration: Duration::from_millis(1500),
+            logs: logs.to_string(),
+        }
+    }
+
+    #[test]
+    fn renders_the_tests_of_the_module() {
+        let reports = [
+            report("it_works", Status::Passed, ""),
+            report(
+                "it_fails",
+                Status::Failed {
+                    hook: Some(Hook {
+                        kind: "before each",
+                        name: "deploy",
+                    }),
+                    message: "expected <true>\nfound \"false\"".to_string(),
+                },
+                "INFO deploying\n",
+            ),
+            report("upgrade_works", Status::Skipped("no node".to_string()), ""),
+        ];
+
+        let suite = render_suite("flipper::tests", &reports, &[("migrate_works", "todo")]);
+        assert_eq!((suite.tests, suite.failures, suite.skipped), (4, 1, 2));
+        assert_eq!(
+            suite.xml,
+            r#"  <testsuite name="flipper::tests" tests="4" failures="1" errors="0" skipped="2" time="4.500">
+    <testcase name="it_works" classname="flipper::tests" time="1.500">
+    </testcase>
+    <testcase name="it_fails" classname="flipper::tests" time="1.500">
+      <failure message="&quot;before each&quot; hook: deploy failed: expected &lt;true&gt;">&quot;before each&quot; hook: deploy failed: expected &lt;true&gt;
+found &quot;false&quot;</failure>
+      <system-out>INFO deploying
+</system-out>
+    </testcase>
+    <testcase name="upgrade_works" classname="flipper::tests" time="1.500">
+      <skipped message="no node"/>
+    </testcase>
+    <testcase name="migrate_works" classname="flipper::tests" time="0.000">
+      <skipped message="pending: todo"/>
+    </testcase>
+  </testsuite>
+"#
+        );
+    }
+
+    #[test]
+    fn renders_a_module_without_tests() {
+        // the ignored and grepped out tests are not reported
+        let suite = render_suite("tests", &[], &[]);
+        assert_eq!(
+            suite.xml,
+            "  <testsuite name=\"tests\" tests=\"0\" failures=\"0\" errors=\"0\" skipped=\"0\" time=\"0.000\">\n  </testsuite>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_the_text() {
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+        assert_eq!(
+            escape("\u{1b}[31mred\u{1b}[0m\tend\r\n"),
+            "\u{FFFD}[31mred\u{FFFD}[0m\tend\r\n"
+        );
+        assert_eq!(
+            escape("null\0 bell\u{7} ünïcode"),
+            "null\u{FFFD} bell\u{FFFD} ünïcode"
+        );
+    }
+}
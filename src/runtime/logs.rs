@@ -0,0 +1,297 @@
+//! Capture of the `tracing` logs of the tests, with the `tracing` feature. Every test runs in a
+//! `test` span, with a child span for each of its hooks and for its body, and its logs are printed
+//! only if it fails. The level of the logs is set with the `PRINTER_LOG` env filter.
+
+#[cfg(feature = "tracing")]
+pub(super) use capture::*;
+
+#[cfg(not(feature = "tracing"))]
+pub(super) use noop::*;
+
+#[cfg(feature = "tracing")]
+mod capture {
+    use std::cell::RefCell;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    use tracing::span::EnteredSpan;
+    use tracing::subscriber::DefaultGuard;
+    use tracing::{Dispatch, Span};
+    use tracing_subscriber::fmt::MakeWriter;
+    use tracing_subscriber::EnvFilter;
+
+    thread_local! {
+        /// The spans of the test running on the current thread.
+        static SPANS: RefCell<Option<Spans>> = const { RefCell::new(None) };
+    }
+
+    struct Spans {
+        test: Span,
+        body: Span,
+        /// The span of the hook, or of the body, the test is in.
+        entered: Option<EnteredSpan>,
+        /// The subscriber capturing the logs, when the test moved to another thread.
+        _default: Option<DefaultGuard>,
+    }
+
+    /// The spans of a test moved out of its thread, with the subscriber capturing its logs.
+    pub(crate) struct Detached(Option<(Dispatch, Span, Span)>);
+
+    /// The logs of a test, captured since it started.
+    pub(crate) struct Capture {
+        logs: Logs,
+        test_span: Option<EnteredSpan>,
+        _default: DefaultGuard,
+    }
+
+    #[derive(Clone, Default)]
+    struct Logs(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Logs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Logs {
+        type Writer = Logs;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Starts capturing the logs of the `test` on the current thread and enters its span.
+    pub(crate) fn test_started(test: &str) -> Capture {
+        let logs = Logs::default();
+        let filter =
+            EnvFilter::try_from_env("PRINTER_LOG").unwrap_or_else(|_| EnvFilter::new("info"));
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let default = tracing::subscriber::set_default(subscriber);
+
+        let test_span = tracing::info_span!("test", name = test);
+        let body = tracing::info_span!(parent: &test_span, "body");
+        let capture = Capture {
+            logs,
+            test_span: Some(test_span.clone().entered()),
+            _default: default,
+        };
+        SPANS.with(|spans| {
+            *spans.borrow_mut() = Some(Spans {
+                test: test_span,
+                entered: Some(body.clone().entered()),
+                body,
+                _default: None,
+            })
+        });
+
+        capture
+    }
+
+    impl Capture {
+        /// Leaves the spans of the test and prints its logs if it `failed`. Returns the logs, for
+        /// the JUnit report.
+        pub(crate) fn finish(mut self, test: &str, failed: bool) -> String {
+            SPANS.with(|spans| spans.borrow_mut().take());
+            self.test_span.take();
+
+            let logs =
+                std::mem::take(&mut *self.logs.0.lock().unwrap_or_else(PoisonError::into_inner));
+            let logs = String::from_utf8_lossy(&logs).into_owned();
+            if failed && !logs.is_empty() {
+                let output = format!("\nlogs of test {}:\n{}", test, logs);
+                let _ = std::io::stderr().lock().write_all(output.as_bytes());
+            }
+
+            logs
+        }
+    }
+
+    /// Leaves the spans of the test running on the current thread, for the test to continue on
+    /// another one.
+    pub(crate) fn detach() -> Detached {
+        let spans = SPANS.with(|spans| spans.borrow_mut().take());
+        Detached(spans.map(|spans| {
+            let dispatch = tracing::dispatcher::get_default(Dispatch::clone);
+            (dispatch, spans.test, spans.body)
+        }))
+    }
+
+    /// Enters the spans of the test taken with [`detach`] on the current thread, capturing its logs
+    /// until the spans are detached again or the test finishes.
+    pub(crate) fn attach(detached: Detached) {
+        let Some((dispatch, test, body)) = detached.0 else {
+            return;
+        };
+
+        let default = tracing::dispatcher::set_default(&dispatch);
+        SPANS.with(|spans| {
+            *spans.borrow_mut() = Some(Spans {
+                test,
+                entered: Some(body.clone().entered()),
+                body,
+                _default: Some(default),
+            })
+        });
+    }
+
+    /// Leaves the span of the body of the current test and enters the span of the hook.
+    pub(crate) fn hook_started(kind: &str, name: &str) {
+        SPANS.with(|spans| {
+            let mut spans = spans.borrow_mut();
+            let Some(spans) = spans.as_mut() else {
+                return;
+            };
+
+            spans.entered.take();
+            let span = match kind {
+                "before each" => {
+                    tracing::info_span!(parent: &spans.test, "before_each", hook = name)
+                }
+                "after each" => tracing::info_span!(parent: &spans.test, "after_each", hook = name),
+                "invariant" => tracing::info_span!(parent: &spans.test, "invariant", hook = name),
+                _ => tracing::info_span!(parent: &spans.test, "hook", hook = name),
+            };
+            spans.entered = Some(span.entered());
+        });
+    }
+
+    /// Leaves the span of the hook and enters the span of the body of the current test again.
+    pub(crate) fn hook_finished() {
+        SPANS.with(|spans| {
+            let mut spans = spans.borrow_mut();
+            let Some(spans) = spans.as_mut() else {
+                return;
+            };
+
+            spans.entered.take();
+            spans.entered = Some(spans.body.clone().entered());
+        });
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod noop {
+    pub(crate) struct Capture;
+
+    pub(crate) struct Detached;
+
+    pub(crate) fn test_started(_test: &str) -> Capture {
+        Capture
+    }
+
+    impl Capture {
+        pub(crate) fn finish(self, _test: &str, _failed: bool) -> String {
+            String::new()
+        }
+    }
+
+    pub(crate) fn detach() -> Detached {
+        Detached
+    }
+
+    pub(crate) fn attach(_detached: Detached) {}
+
+    pub(crate) fn hook_started(_kind: &str, _name: &str) {}
+
+    pub(crate) fn hook_finished() {}
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_the_logs_in_the_spans_of_the_test() {
+        let capture = test_started("it_works");
+        tracing::info!("flipping");
+        hook_started("before each", "deploy");
+        tracing::info!("deploying");
+        hook_finished();
+        tracing::debug!("filtered out by the default level");
+        tracing::warn!("flipped");
+        let logs = capture.finish("tests::it_works", false);
+
+        let lines: Vec<&str> = logs.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", logs);
+        assert!(
+            lines[0].contains(r#"INFO test{name="it_works"}:body: "#)
+                && lines[0].ends_with("flipping"),
+            "{}",
+            logs
+        );
+        assert!(
+            lines[1].contains(r#"INFO test{name="it_works"}:before_each{hook="deploy"}: "#)
+                && lines[1].ends_with("deploying"),
+            "{}",
+            logs
+        );
+        assert!(
+            lines[2].contains(r#"WARN test{name="it_works"}:body: "#)
+                && lines[2].ends_with("flipped"),
+            "{}",
+            logs
+        );
+    }
+
+    #[test]
+    fn captures_the_logs_of_a_test_continuing_on_another_thread() {
+        let capture = test_started("it_times_out");
+        tracing::info!("before");
+        let detached = detach();
+        std::thread::spawn(move || {
+            attach(detached);
+            tracing::info!("on the timeout thread");
+            detach()
+        })
+        .join()
+        .map(attach)
+        .unwrap();
+        tracing::info!("after");
+        let logs = capture.finish("tests::it_times_out", false);
+
+        let messages: Vec<&str> = logs
+            .lines()
+            .map(|line| line.rsplit(": ").next().unwrap())
+            .collect();
+        assert_eq!(
+            messages,
+            ["before", "on the timeout thread", "after"],
+            "{}",
+            logs
+        );
+    }
+
+    #[test]
+    fn captures_the_logs_of_every_test_apart() {
+        let first = test_started("it_works");
+        tracing::info!("first");
+        let _ = first.finish("tests::it_works", false);
+        tracing::info!("between the tests");
+        hook_started("before each", "deploy");
+        hook_finished();
+        let second = test_started("it_fails");
+        tracing::info!("second");
+        let logs = second.finish("tests::it_fails", true);
+
+        let lines: Vec<&str> = logs.lines().collect();
+        assert_eq!(lines.len(), 1, "{}", logs);
+        assert!(
+            lines[0].contains(r#"test{name="it_fails"}:body: "#) && lines[0].ends_with("second"),
+            "{}",
+            logs
+        );
+    }
+}
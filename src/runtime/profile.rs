@@ -0,0 +1,241 @@
+//! The hook profiler, enabled with `PRINTER_PROFILE=1` and the `reporting` feature. Once all of the
+//! tests of a module finish, it prints how long the hooks of the module took compared to the tests.
+
+use std::io::Write;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+
+use super::format_duration;
+use super::reporter::Hook;
+
+static ENABLED: Lazy<bool> = Lazy::new(|| {
+    cfg!(feature = "reporting") && std::env::var("PRINTER_PROFILE").as_deref() == Ok("1")
+});
+
+/// `(thread, hook, duration)` of the finished hooks and tests, the hook is `None` for tests.
+static TIMES: Mutex<Vec<(String, Option<Hook>, Duration)>> = Mutex::new(Vec::new());
+
+/// The order the kinds of the hooks are printed in.
+const KINDS: &[&str] = &["before all", "before each", "invariant", "after each"];
+
+/// Returns true if the profiler is enabled.
+pub(super) fn enabled() -> bool {
+    *ENABLED
+}
+
+/// Records the `duration` of the hook, or of the test if `hook` is `None`, running on the current
+/// thread.
+pub(super) fn record(hook: Option<Hook>, duration: Duration) {
+    if !enabled() {
+        return;
+    }
+
+    let thread = std::thread::current()
+        .name()
+        .unwrap_or("<unnamed>")
+        .to_string();
+    TIMES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((thread, hook, duration));
+}
+
+/// Prints the table of the hooks of the module at `module` path, with the `prefix` libtest shows
+/// before its `tests`.
+pub(super) fn print(module: &str, prefix: &str, tests: &[(&'static str, usize, bool)]) {
+    let times = TIMES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(output) = render(module, prefix, tests, &times) {
+        let _ = std::io::stderr().lock().write_all(output.as_bytes());
+    }
+}
+
+/// Renders the table of the hooks of the module from the recorded `times`, if any of them belong
+/// to the module.
+fn render(
+    module: &str,
+    prefix: &str,
+    tests: &[(&'static str, usize, bool)],
+    times: &[(String, Option<Hook>, Duration)],
+) -> Option<String> {
+    // (hook, runs, total, max), the tests come last
+    let mut rows: Vec<(Option<Hook>, u32, Duration, Duration)> = vec![];
+    for (thread, hook, duration) in times {
+        let Some(test) = thread.strip_prefix(prefix) else {
+            continue;
+        };
+        // the tests of the nested modules are profiled with their own module
+        let name = test.split("::").next().unwrap_or_default();
+        if test.contains("::") && !tests.iter().any(|(test, ..)| *test == name) {
+            continue;
+        }
+
+        match rows.iter_mut().find(|(row_hook, ..)| row_hook == hook) {
+            Some((_, runs, total, max)) => {
+                *runs += 1;
+                *total += *duration;
+                *max = (*max).max(*duration);
+            }
+            None => rows.push((*hook, 1, *duration, *duration)),
+        }
+    }
+    if rows.is_empty() {
+        return None;
+    }
+    rows.sort_by_key(|(hook, ..)| match hook {
+        Some(hook) => (KINDS.iter().position(|kind| *kind == hook.kind), hook.name),
+        None => (Some(KINDS.len()), ""),
+    });
+
+    let test_time = rows
+        .iter()
+        .find(|(hook, ..)| hook.is_none())
+        .map(|(_, _, total, _)| *total)
+        .unwrap_or_default();
+    let labels: Vec<String> = rows
+        .iter()
+        .map(|(hook, ..)| match hook {
+            Some(hook) => hook.to_string(),
+            None => "tests".to_string(),
+        })
+        .collect();
+    let width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let mut output = format!(
+        "\nhook profile of {}:\n  {:width$}  {:>6}  {:>9}  {:>9}  {:>9}  {:>6}\n",
+        module,
+        "hook",
+        "runs",
+        "total",
+        "mean",
+        "max",
+        "share",
+        width = width
+    );
+    for ((hook, runs, total, max), label) in rows.iter().zip(labels) {
+        let share = match hook {
+            Some(_) if !test_time.is_zero() => {
+                format!(
+                    "{:.1}%",
+                    total.as_secs_f64() / test_time.as_secs_f64() * 100.0
+                )
+            }
+            _ => String::new(),
+        };
+        let line = format!(
+            "  {}{:pad$}  {:>6}  {:>9}  {:>9}  {:>9}  {:>6}",
+            label,
+            "",
+            runs,
+            format_duration(*total),
+            format_duration(*total / *runs),
+            format_duration(*max),
+            share,
+            pad = width - label.chars().count()
+        );
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPLOY: Hook = Hook {
+        kind: "before each",
+        name: "deploy",
+    };
+    const CHECK: Hook = Hook {
+        kind: "invariant",
+        name: "check",
+    };
+    const TEARDOWN: Hook = Hook {
+        kind: "after each",
+        name: "teardown",
+    };
+    const CONNECT: Hook = Hook {
+        kind: "before each",
+        name: "connect",
+    };
+
+    fn time(thread: &str, hook: Option<Hook>, millis: u64) -> (String, Option<Hook>, Duration) {
+        (thread.to_string(), hook, Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn orders_the_hooks_by_kind_and_name() {
+        let times = [
+            time("tests::it_works", None, 2000),
+            time("tests::it_works", Some(TEARDOWN), 100),
+            time("tests::it_works", Some(CHECK), 50),
+            time("tests::it_works", Some(DEPLOY), 900),
+            time("tests::it_works", Some(CONNECT), 100),
+            time("tests::it_fails", Some(DEPLOY), 1100),
+            time("tests::it_fails", None, 2000),
+        ];
+        let tests = [("it_works", 1, false), ("it_fails", 1, false)];
+
+        assert_eq!(
+            render("flipper::tests", "tests::", &tests, &times).unwrap(),
+            "
+hook profile of flipper::tests:
+  hook                           runs      total       mean        max   share
+  \"before each\" hook: connect       1      100ms      100ms      100ms    2.5%
+  \"before each\" hook: deploy        2      2.00s      1.00s      1.10s   50.0%
+  \"invariant\" hook: check           1       50ms       50ms       50ms    1.2%
+  \"after each\" hook: teardown       1      100ms      100ms      100ms    2.5%
+  tests                             2      4.00s      2.00s      2.00s
+"
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_other_modules() {
+        let times = [
+            time("tests::it_works", None, 999),
+            time("tests::nested::it_works", Some(DEPLOY), 100),
+            time("other::it_works", Some(DEPLOY), 100),
+            time("tests::cases::case_1", Some(CHECK), 1),
+        ];
+        // rstest cases run in threads named after their case
+        let tests = [("it_works", 1, false), ("cases", 1, false)];
+
+        assert_eq!(
+            render("flipper::tests", "tests::", &tests, &times).unwrap(),
+            "
+hook profile of flipper::tests:
+  hook                       runs      total       mean        max   share
+  \"invariant\" hook: check       1        1ms        1ms        1ms    0.1%
+  tests                         1      999ms      999ms      999ms
+"
+        );
+        assert_eq!(render("flipper::empty", "empty::", &tests, &times), None);
+    }
+
+    #[test]
+    fn leaves_out_the_share_without_tests() {
+        // e.g. the `before_all` hook of a module whose tests are all grepped out
+        let before_all = Hook {
+            kind: "before all",
+            name: "start_node",
+        };
+        let times = [time("tests::printer_before_all", Some(before_all), 1000)];
+
+        assert_eq!(
+            render("flipper::tests", "tests::", &[], &times).unwrap(),
+            "
+hook profile of flipper::tests:
+  hook                             runs      total       mean        max   share
+  \"before all\" hook: start_node       1      1.00s      1.00s      1.00s
+"
+        );
+    }
+}
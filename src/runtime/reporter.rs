@@ -0,0 +1,438 @@
+//! The mocha-like `spec` reporter, enabled with `PRINTER_REPORTER=spec` and the `reporting` feature.
+//! libtest runs the tests in parallel, so the tests of a module are reported together once all of
+//! them finish.
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::{Mutex, Once, PoisonError};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+use super::{events, format_duration, junit, panic_message};
+
+static ENABLED: Lazy<bool> = Lazy::new(|| {
+    cfg!(feature = "reporting") && std::env::var("PRINTER_REPORTER").as_deref() == Ok("spec")
+});
+
+/// `(thread, hook, message)` of the hooks that failed outside of the tests, e.g. `before_all`.
+static HOOK_FAILURES: Mutex<Vec<(String, Hook, String)>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+/// Events of the test running on the current thread.
+#[derive(Default)]
+pub(super) struct State {
+    /// Whether a test of a suite is running on the thread.
+    in_test: bool,
+    /// The hook that started, but didn't finish, with its start.
+    pub hook: Option<Hook>,
+    pub hook_start: Option<Instant>,
+    /// The message of the last panic.
+    pub panic: Option<String>,
+    /// The reason the test was skipped with.
+    pub skipped: Option<String>,
+    /// Whether the test returns a value, which is checked with
+    /// [`TestGuard::check`](super::TestGuard::check).
+    returns_value: bool,
+    /// The failure the test returned, once its value is checked.
+    returned: Option<Option<String>>,
+}
+
+impl State {
+    /// Returns the failure the test returned. A test returning a value which finishes before the
+    /// value is checked, without being skipped, returned an error early with `?`.
+    pub fn returned_failure(&self) -> Option<String> {
+        match &self.returned {
+            Some(failure) => failure.clone(),
+            None if self.returns_value && self.skipped.is_none() => {
+                Some("returned an error early with `?`".to_string())
+            }
+            None => None,
+        }
+    }
+}
+
+/// How a test of a suite ended.
+pub(super) enum Status {
+    Passed,
+    Failed { hook: Option<Hook>, message: String },
+    Skipped(String),
+}
+
+/// A finished test of a suite.
+pub(super) struct Report {
+    pub test: &'static str,
+    /// The path of the test in the module, including the case of rstest tests.
+    pub path: String,
+    pub status: Status,
+    pub duration: Duration,
+    /// The `tracing` logs captured during the test, with the `tracing` feature.
+    pub logs: String,
+}
+
+/// A hook function of the `kind`, e.g. the `before each` hook `deploy`.
+#[derive(Clone, Copy, PartialEq)]
+pub(super) struct Hook {
+    pub kind: &'static str,
+    pub name: &'static str,
+}
+
+impl std::fmt::Display for Hook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\" hook: {}", self.kind, self.name)
+    }
+}
+
+/// Returns true if the `spec` reporter is enabled.
+pub(super) fn enabled() -> bool {
+    *ENABLED
+}
+
+/// Returns true if the finished tests are reported, by the `spec` reporter, the JUnit report or the
+/// events.
+pub(super) fn collects_reports() -> bool {
+    enabled() || junit::enabled() || events::enabled()
+}
+
+/// Resets the events of the current thread for a new test.
+pub(super) fn test_started() {
+    install_panic_hook();
+    STATE.with(|state| {
+        *state.borrow_mut() = State {
+            in_test: true,
+            ..State::default()
+        }
+    });
+}
+
+/// Returns the events of the test that finished on the current thread.
+pub(super) fn test_finished() -> State {
+    STATE.with(|state| state.take())
+}
+
+/// Moves the events of the test out of the current thread, for the test to continue on another one.
+pub(super) fn take_state() -> State {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let in_test = state.in_test;
+        std::mem::replace(
+            &mut *state,
+            State {
+                in_test,
+                ..State::default()
+            },
+        )
+    })
+}
+
+/// Moves the events of the test taken with [`take_state`] to the current thread.
+pub(super) fn restore_state(state: State) {
+    STATE.with(|current| *current.borrow_mut() = state);
+}
+
+pub(super) fn hook_started(hook: Hook) {
+    install_panic_hook();
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.hook = Some(hook);
+        state.hook_start = Some(Instant::now());
+    });
+}
+
+/// Returns the hook that finished with its duration.
+pub(super) fn hook_finished() -> Option<(Hook, Duration)> {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        Some((state.hook.take()?, state.hook_start.take()?.elapsed()))
+    })
+}
+
+pub(super) fn test_skipped(reason: &str) {
+    STATE.with(|state| state.borrow_mut().skipped = Some(reason.to_string()));
+}
+
+/// Marks the test running on the current thread as returning a value.
+pub(super) fn test_returns_value() {
+    STATE.with(|state| state.borrow_mut().returns_value = true);
+}
+
+/// Records the `failure` the test running on the current thread returned, if any.
+pub(super) fn test_returned(failure: Option<String>) {
+    STATE.with(|state| state.borrow_mut().returned = Some(failure));
+}
+
+/// Returns true if the test running on the current thread neither returned a failure nor was
+/// skipped.
+pub(super) fn passed() -> bool {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.skipped.is_none() && state.returned_failure().is_none()
+    })
+}
+
+/// Records the messages of the panics, chaining the default hook printing them.
+fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    if !collects_reports() {
+        return;
+    }
+
+    INSTALLED.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let message = panic_message(info.payload());
+            let hook = STATE.with(|state| {
+                let mut state = state.borrow_mut();
+                state.panic = Some(message.clone());
+                let duration = state.hook_start.map(|start| start.elapsed());
+                (!state.in_test)
+                    .then_some(state.hook.zip(duration))
+                    .flatten()
+            });
+            if let Some((hook, duration)) = hook {
+                super::hook_ended(hook, duration, Some(&message));
+                let thread = std::thread::current()
+                    .name()
+                    .unwrap_or("<unnamed>")
+                    .to_string();
+                let mut failures = HOOK_FAILURES.lock().unwrap_or_else(PoisonError::into_inner);
+                // properties can run their hooks many times, the hook is reported once
+                if !failures
+                    .iter()
+                    .any(|(failed, failed_hook, _)| *failed == thread && *failed_hook == hook)
+                {
+                    failures.push((thread, hook, message));
+                }
+            }
+
+            default_hook(info);
+        }));
+    });
+}
+
+/// Prints the tests of the module at `module` path, with the `prefix` libtest shows before the
+/// tests of the module, followed by the summary of the module.
+pub(super) fn print(
+    module: &str,
+    prefix: &str,
+    reports: &[Report],
+    pending: &[(&'static str, &'static str)],
+) {
+    let hook_failures: Vec<(String, Hook, String)> = HOOK_FAILURES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .filter_map(|(thread, hook, message)| {
+            let test = thread.strip_prefix(prefix)?;
+            (!test.contains("::")).then(|| (title(test), *hook, message.clone()))
+        })
+        .collect();
+
+    let output = render(module, reports, pending, hook_failures);
+    let _ = std::io::stderr().lock().write_all(output.as_bytes());
+}
+
+/// Renders the report of the module at `module` path, with the `(test, hook, message)` of the
+/// hooks that failed outside of the tests.
+fn render(
+    module: &str,
+    reports: &[Report],
+    pending: &[(&'static str, &'static str)],
+    hook_failures: Vec<(String, Hook, String)>,
+) -> String {
+    let mut output = String::from("\n");
+    let mut indent = 1;
+    for segment in module.split("::") {
+        output.push_str(&format!(
+            "{:indent$}{}\n",
+            "",
+            title(segment),
+            indent = indent * 2
+        ));
+        indent += 1;
+    }
+
+    let mut failures = vec![];
+    let (mut passing, mut pending_num) = (0, pending.len());
+    for report in reports {
+        let test = title(&report.path);
+        let duration = format_duration(report.duration);
+        let line = match &report.status {
+            Status::Passed => {
+                passing += 1;
+                format!("✓ {} ({})", test, duration)
+            }
+            Status::Failed { hook, message } => {
+                failures.push((test.clone(), *hook, message.clone()));
+                format!("✗ {} ({})", test, duration)
+            }
+            Status::Skipped(reason) => {
+                pending_num += 1;
+                format!("- {} (skipped: {})", test, reason)
+            }
+        };
+        output.push_str(&format!("{:indent$}{}\n", "", line, indent = indent * 2));
+    }
+    for (test, reason) in pending {
+        let line = format!("- {} (pending: {})", title(test), reason);
+        output.push_str(&format!("{:indent$}{}\n", "", line, indent = indent * 2));
+    }
+    let hook_failures: Vec<(String, String)> = hook_failures
+        .into_iter()
+        .map(|(test, hook, message)| match hook.kind {
+            // `before_all` runs as a test of its own
+            "before all" => (hook.to_string(), message),
+            _ => (format!("{} for \"{}\"", hook, test), message),
+        })
+        .collect();
+    for (hook, _) in &hook_failures {
+        let line = format!("✗ {}", hook);
+        output.push_str(&format!("{:indent$}{}\n", "", line, indent = indent * 2));
+    }
+
+    let failing = failures.len() + hook_failures.len();
+    output.push_str(&format!("\n  {} passing", passing));
+    if failing > 0 {
+        output.push_str(&format!(", {} failing", failing));
+    }
+    if pending_num > 0 {
+        output.push_str(&format!(", {} pending", pending_num));
+    }
+    output.push('\n');
+
+    let module_title = title(module);
+    let failures = failures
+        .into_iter()
+        .map(|(test, hook, message)| match hook {
+            Some(hook) => (format!("{} for \"{}\"", hook, test), message),
+            None => (test, message),
+        });
+    for (idx, (failure, message)) in failures.chain(hook_failures).enumerate() {
+        output.push_str(&format!(
+            "\n  {}) {}\n       {}:\n     {}\n",
+            idx + 1,
+            module_title,
+            failure,
+            message.replace('\n', "\n     ")
+        ));
+    }
+
+    output
+}
+
+fn title(test: &str) -> String {
+    test.replace("::", " ").replace('_', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(path: &str, status: Status, millis: u64) -> Report {
+        Report {
+            test: "",
+            path: path.to_string(),
+            status,
+            duration: Duration::from_millis(millis),
+            logs: String::new(),
+        }
+    }
+
+    const DEPLOY: Hook = Hook {
+        kind: "before each",
+        name: "deploy",
+    };
+
+    #[test]
+    fn renders_the_tests_of_the_module() {
+        let reports = [
+            report("it_works", Status::Passed, 1200),
+            report(
+                "it_works_too",
+                Status::Failed {
+                    hook: Some(DEPLOY),
+                    message: "instantiate failed:\nno node".to_string(),
+                },
+                850,
+            ),
+            report("upgrade_works", Status::Skipped("no node".to_string()), 0),
+        ];
+
+        let output = render(
+            "flipper::e2e_tests",
+            &reports,
+            &[("handles_reentrancy", "todo")],
+            vec![],
+        );
+        assert_eq!(
+            output,
+            "
+  flipper
+    e2e tests
+      ✓ it works (1.20s)
+      ✗ it works too (850ms)
+      - upgrade works (skipped: no node)
+      - handles reentrancy (pending: todo)
+
+  1 passing, 1 failing, 2 pending
+
+  1) flipper e2e tests
+       \"before each\" hook: deploy for \"it works too\":
+     instantiate failed:
+     no node
+"
+        );
+    }
+
+    #[test]
+    fn renders_the_failures_of_the_hooks_outside_of_the_tests() {
+        let before_all = Hook {
+            kind: "before all",
+            name: "start_node",
+        };
+        let reports = [report("it_works", Status::Passed, 5)];
+
+        let output = render(
+            "tests",
+            &reports,
+            &[],
+            vec![
+                (
+                    "prop works".to_string(),
+                    DEPLOY,
+                    "deploy failed".to_string(),
+                ),
+                ("before all".to_string(), before_all, "no node".to_string()),
+            ],
+        );
+        assert_eq!(
+            output,
+            "
+  tests
+    ✓ it works (5ms)
+    ✗ \"before each\" hook: deploy for \"prop works\"
+    ✗ \"before all\" hook: start_node
+
+  1 passing, 2 failing
+
+  1) tests
+       \"before each\" hook: deploy for \"prop works\":
+     deploy failed
+
+  2) tests
+       \"before all\" hook: start_node:
+     no node
+"
+        );
+    }
+
+    #[test]
+    fn renders_a_module_without_tests() {
+        // the ignored and grepped out tests are not reported
+        let output = render("tests", &[], &[], vec![]);
+        assert_eq!(output, "\n  tests\n\n  0 passing\n");
+    }
+}
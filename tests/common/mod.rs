@@ -0,0 +1,76 @@
+//! Runs the printer modules of an integration test binary in a child process, with the libtest
+//! arguments and the environment the tests are about.
+
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long the child process may run, e.g. before its tests are considered deadlocked.
+const DEADLINE: Duration = Duration::from_secs(60);
+
+/// Runs the tests of the current test binary matching `filter` with the extra libtest `args` and
+/// environment variables. Panics if the tests don't finish before the deadline.
+pub fn run_tests(filter: &str, args: &[&str], envs: &[(&str, &str)]) -> Output {
+    let mut command = Command::new(std::env::current_exe().unwrap());
+    for (var, _) in std::env::vars() {
+        if var.starts_with("PRINTER_") || var == "RUST_TEST_THREADS" {
+            command.env_remove(var);
+        }
+    }
+    let mut child = command
+        .arg(filter)
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run the test binary");
+
+    // the pipes are drained while the child runs, so that it never blocks on a full pipe
+    let mut stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+    let stdout = std::thread::spawn(move || {
+        let mut output = vec![];
+        let _ = stdout.read_to_end(&mut output);
+        output
+    });
+    let stderr = std::thread::spawn(move || {
+        let mut output = vec![];
+        let _ = stderr.read_to_end(&mut output);
+        output
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if start.elapsed() > DEADLINE {
+            let _ = child.kill();
+            let _ = child.wait();
+            panic!(
+                "the tests matching `{}` didn't finish in {:?}:\n{}{}",
+                filter,
+                DEADLINE,
+                String::from_utf8_lossy(&stdout.join().unwrap()),
+                String::from_utf8_lossy(&stderr.join().unwrap())
+            );
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    Output {
+        status,
+        stdout: stdout.join().unwrap(),
+        stderr: stderr.join().unwrap(),
+    }
+}
+
+/// Returns the stdout and the stderr of the child process.
+pub fn output_text(output: &Output) -> String {
+    format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+}
@@ -0,0 +1,39 @@
+mod common;
+
+#[printer::printer]
+mod rows {
+    #[before_each(only)]
+    fn setup() {
+        println!("before each row ({}, {}, {})", a, b, expected);
+    }
+
+    #[each((1, 2, 3), (-1, -2, -3))]
+    #[test]
+    fn add_works(a: i32, b: i32, expected: i32) {
+        assert_eq!(a + b, expected);
+    }
+
+    #[each("alice", "bob")]
+    #[test]
+    fn name_works(name: &str) {
+        assert!(!name.is_empty());
+    }
+}
+
+#[test]
+fn tests_are_generated_for_every_row() {
+    let output = common::run_tests("rows::", &["--show-output"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    for test in [
+        "rows::add_works_1_2_3",
+        "rows::add_works_minus_1_minus_2_minus_3",
+        "rows::name_works_alice",
+        "rows::name_works_bob",
+    ] {
+        assert!(text.contains(&format!("test {} ... ok", test)), "{}", text);
+    }
+    assert!(text.contains("before each row (1, 2, 3)"), "{}", text);
+    assert!(text.contains("before each row (-1, -2, -3)"), "{}", text);
+}
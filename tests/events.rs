@@ -0,0 +1,79 @@
+#![cfg(feature = "reporting")]
+
+mod common;
+
+#[printer::printer]
+mod events {
+    #[before_each]
+    fn deploy() {
+        let deployed = true;
+    }
+
+    #[test]
+    fn a_works() {
+        assert!(deployed);
+    }
+
+    #[test]
+    #[ignore]
+    fn b_ignored_works() {
+        assert!(deployed);
+    }
+
+    #[test]
+    fn c_upgrade_works() {
+        assert!(deployed);
+    }
+}
+
+#[test]
+fn events_of_the_tests_that_run() {
+    let path = std::env::temp_dir().join(format!("printer-events-{}.jsonl", std::process::id()));
+    let output = common::run_tests(
+        "events::",
+        &[],
+        &[
+            ("PRINTER_EVENTS", path.to_str().unwrap()),
+            ("PRINTER_GREP", "a works"),
+        ],
+    );
+    let text = common::output_text(&output);
+    let events = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(output.status.success(), "{}", text);
+    let names: Vec<&str> = events
+        .lines()
+        .map(|line| {
+            let start = "{\"event\":\"".len();
+            &line[start..start + line[start..].find('"').unwrap()]
+        })
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "suite_started",
+            "test_started",
+            "hook_started",
+            "hook_finished",
+            "test_finished",
+            "suite_finished"
+        ],
+        "{}",
+        events
+    );
+    assert!(
+        events.contains(
+            "\"test\":\"events::a_works\",\"hook\":\"before each\",\"hook_name\":\"deploy\""
+        ),
+        "{}",
+        events
+    );
+    assert!(
+        events.contains("\"test\":\"events::a_works\",\"duration\":"),
+        "{}",
+        events
+    );
+    assert!(!events.contains("b_ignored_works"), "{}", events);
+    assert!(!events.contains("c_upgrade_works"), "{}", events);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+static ALICE: AtomicUsize = AtomicUsize::new(0);
+static BOB: AtomicUsize = AtomicUsize::new(0);
+static NODE: AtomicUsize = AtomicUsize::new(0);
+
+/// Uses the resources for a while, failing the test if another test uses any of them meanwhile.
+fn use_resources(resources: &[&AtomicUsize]) {
+    let users: Vec<usize> = resources
+        .iter()
+        .map(|resource| resource.fetch_add(1, Ordering::SeqCst))
+        .collect();
+    std::thread::sleep(Duration::from_millis(50));
+    for resource in resources {
+        resource.fetch_sub(1, Ordering::SeqCst);
+    }
+    assert!(users.iter().all(|users| *users == 0), "{:?}", users);
+}
+
+#[printer::printer]
+mod first {
+    use super::{use_resources, ALICE, BOB};
+
+    #[exclusive(alice)]
+    #[test]
+    fn alice_works() {
+        use_resources(&[&ALICE]);
+    }
+
+    #[exclusive(alice, bob)]
+    #[test]
+    fn alice_and_bob_work() {
+        use_resources(&[&ALICE, &BOB]);
+    }
+}
+
+#[printer::printer]
+mod second {
+    use super::{use_resources, ALICE, BOB};
+
+    #[exclusive(bob, alice)]
+    #[test]
+    fn bob_and_alice_work() {
+        use_resources(&[&BOB, &ALICE]);
+    }
+
+    #[exclusive(bob)]
+    #[printer::tokio::test(crate = "printer::tokio")]
+    async fn bob_works() {
+        use_resources(&[&BOB]);
+    }
+}
+
+#[printer::printer]
+mod hooks {
+    use super::{use_resources, NODE};
+
+    #[before_each]
+    #[exclusive(node)]
+    fn setup() {
+        use_resources(&[&NODE]);
+    }
+
+    #[test]
+    fn first_works() {}
+
+    #[test]
+    fn second_works() {}
+
+    #[test]
+    fn third_works() {}
+}
+
+#[test]
+fn tests_locking_a_resource_never_overlap() {
+    let output = common::run_tests("", &["--test-threads=4", "--skip", "tests_"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("7 passed"), "{}", text);
+}
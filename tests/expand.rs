@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+mod first {
+    #[printer::printer(expand)]
+    mod tests {
+        #[test]
+        fn first_works() {}
+    }
+}
+
+mod second {
+    #[printer::printer(expand)]
+    mod tests {
+        #[test]
+        fn second_works() {}
+    }
+}
+
+#[test]
+fn same_named_modules_are_expanded_apart() {
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"));
+    let dir = target_dir.join("printer-expand/expand/tests/expand");
+    let expansions: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+
+    assert!(
+        expansions
+            .iter()
+            .any(|expansion| expansion.contains("fn first_works")
+                && !expansion.contains("fn second_works")),
+        "{:?}",
+        expansions
+    );
+    assert!(
+        expansions
+            .iter()
+            .any(|expansion| expansion.contains("fn second_works")
+                && !expansion.contains("fn first_works")),
+        "{:?}",
+        expansions
+    );
+}
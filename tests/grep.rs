@@ -0,0 +1,27 @@
+#![cfg(feature = "reporting")]
+
+mod common;
+
+#[printer::printer]
+mod grep {
+    #[test]
+    fn a_deploy_works() {}
+
+    #[test]
+    fn b_upgrade_works() {}
+}
+
+#[test]
+fn grepped_out_tests_are_not_reported() {
+    let output = common::run_tests(
+        "grep::",
+        &["--test-threads=1"],
+        &[("PRINTER_GREP", "upgrade"), ("PRINTER_REPORTER", "spec")],
+    );
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("✓ b upgrade works"), "{}", text);
+    assert!(text.contains("1 passing"), "{}", text);
+    assert!(!text.contains("a deploy works"), "{}", text);
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "reporting")]
+
+mod common;
+
+#[printer::printer]
+mod junit {
+    #[test]
+    fn a_works() {
+        #[cfg(feature = "tracing")]
+        printer::tracing::info!("deploying <flipper>");
+    }
+
+    #[test]
+    #[ignore]
+    fn b_ignored_works() {}
+
+    #[test]
+    fn c_upgrade_works() {
+        printer::skip!("no node");
+    }
+}
+
+#[test]
+fn junit_report_holds_the_tests_that_run() {
+    let path = std::env::temp_dir().join(format!("printer-junit-{}.xml", std::process::id()));
+    let output = common::run_tests("junit::", &[], &[("PRINTER_JUNIT", path.to_str().unwrap())]);
+    let text = common::output_text(&output);
+    let report = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(report.starts_with("<?xml"), "{}", report);
+    assert!(
+        report.contains("<testsuite name=\"junit::junit\" tests=\"2\" failures=\"0\" errors=\"0\" skipped=\"1\""),
+        "{}",
+        report
+    );
+    assert!(
+        report.contains("<testcase name=\"c_upgrade_works\" classname=\"junit::junit\""),
+        "{}",
+        report
+    );
+    assert!(
+        report.contains("<skipped message=\"no node\"/>"),
+        "{}",
+        report
+    );
+    assert!(!report.contains("b_ignored_works"), "{}", report);
+    if cfg!(feature = "tracing") {
+        assert!(
+            report.contains("<system-out>") && report.contains("deploying &lt;flipper&gt;"),
+            "{}",
+            report
+        );
+    } else {
+        assert!(!report.contains("<system-out>"), "{}", report);
+    }
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+#[printer::printer]
+mod logs {
+    use printer::tracing::info;
+
+    #[before_each]
+    fn deploy() {
+        info!("deploying");
+    }
+
+    #[test]
+    fn a_works() {
+        info!("flipping");
+    }
+
+    #[test]
+    #[ignore]
+    fn b_fails() {
+        info!("failing");
+        panic!("flipped");
+    }
+}
+
+#[test]
+fn logs_of_the_passing_tests_are_not_printed() {
+    let output = common::run_tests("logs::", &[], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(!text.contains("logs of test"), "{}", text);
+}
+
+#[test]
+fn logs_of_the_failing_tests_are_printed() {
+    let output = common::run_tests("logs::", &["--ignored"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(!output.status.success(), "{}", text);
+    assert!(text.contains("logs of test logs::b_fails:\n"), "{}", text);
+    assert!(
+        text.contains(r#"test{name="b_fails"}:before_each{hook="deploy"}: "#),
+        "{}",
+        text
+    );
+    assert!(text.contains(r#"test{name="b_fails"}:body: "#), "{}", text);
+    assert!(!text.contains("flipping"), "{}", text);
+}
+
+#[test]
+fn grepped_out_tests_are_not_captured() {
+    let output = common::run_tests(
+        "logs::",
+        &["--include-ignored"],
+        &[("PRINTER_GREP", "a works")],
+    );
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(!text.contains("logs of test"), "{}", text);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+#[printer::printer(matrix(a = [1, 2], b = [10, 20]))]
+mod grid {
+    #[before_each]
+    fn setup() {
+        println!("before each combination ({}, {})", a, b);
+        let sum = a + b;
+    }
+
+    #[test]
+    fn sum_works() {
+        assert_eq!(sum - b, a);
+    }
+}
+
+#[test]
+fn tests_are_generated_for_every_combination() {
+    let output = common::run_tests("grid::", &["--show-output"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    for combination in ["a_1_b_10", "a_1_b_20", "a_2_b_10", "a_2_b_20"] {
+        assert!(
+            text.contains(&format!("test grid::{}::sum_works ... ok", combination)),
+            "{}",
+            text
+        );
+    }
+    for combination in ["(1, 10)", "(1, 20)", "(2, 10)", "(2, 20)"] {
+        assert!(
+            text.contains(&format!("before each combination {}", combination)),
+            "{}",
+            text
+        );
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+
+// the tests are named so that they sort after `printer_before_all`, since with a single test thread
+// libtest runs the tests in the alphabetical order
+#[allow(deprecated)]
+#[printer::printer]
+mod focused {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static DEPLOYED: AtomicBool = AtomicBool::new(false);
+
+    #[before_all]
+    fn deploy() {
+        DEPLOYED.store(true, Ordering::SeqCst);
+    }
+
+    #[only]
+    #[test]
+    fn test_focused() {
+        assert!(DEPLOYED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_not_focused() {}
+}
+
+#[test]
+fn not_focused_tests_run_with_include_ignored() {
+    let output = common::run_tests("focused::", &["--include-ignored"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(
+        text.contains("test focused::test_not_focused ... ok"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("test focused::test_focused ... ok"),
+        "{}",
+        text
+    );
+}
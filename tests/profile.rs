@@ -0,0 +1,82 @@
+#![cfg(feature = "reporting")]
+
+mod common;
+
+#[printer::printer]
+mod profile {
+    #[before_each]
+    fn deploy() {
+        let deployed = true;
+    }
+
+    #[test]
+    fn a_works() {
+        assert!(deployed);
+    }
+
+    #[test]
+    #[ignore]
+    fn b_ignored_works() {
+        assert!(deployed);
+    }
+
+    #[test]
+    fn c_upgrade_works() {
+        assert!(deployed);
+    }
+}
+
+/// Returns the number of the runs in the row of the table starting with `label`.
+fn runs(text: &str, label: &str) -> Option<u32> {
+    let line = text
+        .lines()
+        .find(|line| line.trim_start().starts_with(label))?;
+    line[line.find(label)? + label.len()..]
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[test]
+fn profile_of_the_tests_that_run() {
+    let output = common::run_tests(
+        "profile::",
+        &[],
+        &[("PRINTER_PROFILE", "1"), ("PRINTER_GREP", "a works")],
+    );
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(
+        text.contains("hook profile of profile::profile:"),
+        "{}",
+        text
+    );
+    assert_eq!(
+        runs(&text, "\"before each\" hook: deploy"),
+        Some(1),
+        "{}",
+        text
+    );
+    assert_eq!(runs(&text, "tests"), Some(1), "{}", text);
+}
+
+#[test]
+fn profile_of_the_ignored_tests_that_run() {
+    let output = common::run_tests(
+        "profile::",
+        &["--include-ignored"],
+        &[("PRINTER_PROFILE", "1")],
+    );
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert_eq!(
+        runs(&text, "\"before each\" hook: deploy"),
+        Some(3),
+        "{}",
+        text
+    );
+    assert_eq!(runs(&text, "tests"), Some(3), "{}", text);
+}
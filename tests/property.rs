@@ -0,0 +1,64 @@
+mod common;
+
+#[printer::printer]
+mod per_case {
+    use proptest::prelude::*;
+
+    #[before_each]
+    fn setup() {
+        println!("before each case of the property");
+        let limit = 100u32;
+    }
+
+    proptest! {
+        #[test]
+        fn below_limit(x in 0..100u32) {
+            prop_assert!(x < limit);
+        }
+    }
+}
+
+#[printer::printer(property_hooks = per_property)]
+mod per_property {
+    use proptest::prelude::*;
+
+    #[before_each]
+    fn setup() {
+        println!("before each property");
+        let limit = 100u32;
+    }
+
+    proptest! {
+        #[test]
+        fn below_limit(x in 0..100u32) {
+            prop_assert!(x < limit);
+        }
+    }
+}
+
+#[test]
+fn hooks_run_for_every_case() {
+    let output = common::run_tests("per_case::", &["--show-output"], &[("PROPTEST_CASES", "5")]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert_eq!(
+        text.matches("before each case of the property").count(),
+        5,
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn hooks_run_once_per_property() {
+    let output = common::run_tests(
+        "per_property::",
+        &["--show-output"],
+        &[("PROPTEST_CASES", "5")],
+    );
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert_eq!(text.matches("before each property").count(), 1, "{}", text);
+}
@@ -0,0 +1,32 @@
+#![cfg(not(feature = "reporting"))]
+
+mod common;
+
+#[printer::printer]
+mod plain {
+    #[test]
+    fn first_works() {}
+
+    #[test]
+    fn second_works() {}
+}
+
+#[test]
+fn reporting_variables_are_reported_without_the_feature() {
+    let output = common::run_tests(
+        "plain::",
+        &[],
+        &[("PRINTER_GREP", "first"), ("PRINTER_JUNIT", "junit.xml")],
+    );
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert_eq!(
+        text.matches("warning: PRINTER_GREP, PRINTER_JUNIT ignored without the `reporting` feature of printer")
+            .count(),
+        1,
+        "{}",
+        text
+    );
+    assert!(text.contains("test plain::second_works ... ok"), "{}", text);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+#[printer::printer]
+mod flaky {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static SYNC_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    static ASYNC_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    #[before_each]
+    fn setup() {
+        println!("before each attempt");
+    }
+
+    #[retries(2)]
+    #[test]
+    fn passes_on_second_attempt() {
+        assert!(SYNC_ATTEMPTS.fetch_add(1, Ordering::SeqCst) > 0);
+    }
+
+    #[retries(2)]
+    #[printer::tokio::test(crate = "printer::tokio")]
+    async fn returns_ok_on_second_attempt() -> Result<(), String> {
+        if ASYNC_ATTEMPTS.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err("not yet".to_string());
+        }
+        Ok(())
+    }
+
+    #[retries(1)]
+    #[test]
+    #[should_panic(expected = "always")]
+    fn fails_every_attempt() {
+        panic!("always");
+    }
+}
+
+#[test]
+fn tests_pass_on_a_later_attempt() {
+    let output = common::run_tests("flaky::", &["--show-output"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    for message in [
+        "test `passes_on_second_attempt` failed on attempt 1 of 3: assertion failed",
+        "test `passes_on_second_attempt` passed on attempt 2 of 3",
+        "test `returns_ok_on_second_attempt` failed on attempt 1 of 3: \"not yet\"",
+        "test `returns_ok_on_second_attempt` passed on attempt 2 of 3",
+        "test `fails_every_attempt` failed on attempt 2 of 2: always",
+    ] {
+        assert!(text.contains(message), "{}", text);
+    }
+    // every test makes two attempts, each with its hooks
+    assert!(!text.contains("attempt 3 of"), "{}", text);
+    assert_eq!(text.matches("before each attempt").count(), 6, "{}", text);
+}
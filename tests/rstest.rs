@@ -0,0 +1,46 @@
+mod common;
+
+// the tests are named so that they sort after `printer_before_all`, since with a single test thread
+// libtest runs the tests in the alphabetical order
+#[printer::printer]
+mod cases {
+    use rstest::rstest;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static DEPLOYED: AtomicBool = AtomicBool::new(false);
+
+    #[before_all]
+    fn deploy() {
+        DEPLOYED.store(true, Ordering::SeqCst);
+    }
+
+    #[before_each]
+    fn setup() {
+        assert!(DEPLOYED.load(Ordering::SeqCst));
+        println!("before each case ({}, {})", a, b);
+        let expected = a + b;
+    }
+
+    #[rstest]
+    #[case(1, 2)]
+    #[case(-1, -2)]
+    fn sum_works(#[case] a: i32, #[case] b: i32) {
+        assert_eq!(expected, b + a);
+    }
+}
+
+#[test]
+fn hooks_run_for_every_case() {
+    let output = common::run_tests("cases::", &["--show-output", "--test-threads=1"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("before each case (1, 2)"), "{}", text);
+    assert!(text.contains("before each case (-1, -2)"), "{}", text);
+    // `before_all` sends a message for every case, otherwise the second one waits forever
+    assert!(
+        text.contains("test cases::sum_works::case_2 ... ok"),
+        "{}",
+        text
+    );
+}
@@ -0,0 +1,110 @@
+mod common;
+
+// libtest starts the tests in the alphabetical order, which is the order of their declaration
+#[printer::printer(sequential)]
+mod ordered {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static STEP: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn a_deploy_works() {
+        assert_eq!(STEP.fetch_add(1, Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn b_upgrade_works() {
+        assert_eq!(STEP.fetch_add(1, Ordering::SeqCst), 1);
+    }
+}
+
+// the tests are ignored, since they fail on a single test thread, and run by the tests below
+#[printer::printer(sequential)]
+mod unordered {
+    #[test]
+    #[ignore]
+    fn upgrade_works() {}
+
+    #[test]
+    #[ignore]
+    fn migrate_works() {}
+}
+
+// the dependencies sort before the tests depending on them
+#[printer::printer]
+mod dependencies {
+    #[test]
+    #[ignore]
+    fn deploy_works() {}
+
+    #[depends_on(deploy_works)]
+    #[test]
+    fn upgrade_works() {
+        panic!("the dependency is ignored");
+    }
+
+    #[test]
+    fn install_works() {
+        printer::skip!("not today");
+    }
+
+    #[depends_on(install_works)]
+    #[test]
+    fn migrate_works() {
+        panic!("the dependency is skipped");
+    }
+}
+
+#[test]
+fn ordered_tests_run_on_a_single_thread() {
+    let output = common::run_tests("ordered::", &["--test-threads=1"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+}
+
+#[test]
+fn unordered_tests_fail_instead_of_hanging_on_a_single_thread() {
+    let output = common::run_tests("unordered::", &["--ignored", "--test-threads=1"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(!output.status.success(), "{}", text);
+    assert!(
+        text.contains(
+            "test `migrate_works` has to wait for `upgrade_works`, which didn't start yet"
+        ),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("test unordered::upgrade_works ... ok"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn unordered_tests_wait_with_more_threads() {
+    let output = common::run_tests("unordered::", &["--ignored", "--test-threads=2"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+}
+
+#[test]
+fn tests_depending_on_tests_that_dont_run_are_skipped() {
+    let output = common::run_tests("dependencies::", &["--test-threads=1"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(
+        text.contains("upgrade_works: skipped because `deploy_works` was filtered out"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("migrate_works: skipped because `install_works` did not pass"),
+        "{}",
+        text
+    );
+}
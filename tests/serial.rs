@@ -0,0 +1,78 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Runs the body of a test, failing it if more than `limit` tests of its module run at once.
+fn run_limited(running: &AtomicUsize, limit: usize) {
+    let others = running.fetch_add(1, Ordering::SeqCst);
+    std::thread::sleep(Duration::from_millis(50));
+    running.fetch_sub(1, Ordering::SeqCst);
+    assert!(others < limit, "{} other tests were running", others);
+}
+
+#[printer::printer(serial)]
+mod serial {
+    use std::sync::atomic::AtomicUsize;
+
+    static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+    #[before_each]
+    fn setup() {
+        super::run_limited(&RUNNING, 1);
+    }
+
+    #[test]
+    fn first_works() {}
+
+    #[test]
+    fn second_works() {}
+
+    #[test]
+    fn third_works() {}
+
+    #[printer::tokio::test(crate = "printer::tokio")]
+    async fn async_works() {}
+}
+
+#[printer::printer(max_parallel = 2)]
+mod limited {
+    use std::sync::atomic::AtomicUsize;
+
+    static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+    #[after_each]
+    fn teardown() {
+        super::run_limited(&RUNNING, 2);
+    }
+
+    #[test]
+    fn first_works() {}
+
+    #[test]
+    fn second_works() {}
+
+    #[test]
+    fn third_works() {}
+
+    #[test]
+    fn fourth_works() {}
+}
+
+#[test]
+fn serial_tests_never_overlap() {
+    let output = common::run_tests("serial::", &["--test-threads=4"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("4 passed"), "{}", text);
+}
+
+#[test]
+fn at_most_max_parallel_tests_overlap() {
+    let output = common::run_tests("limited::", &["--test-threads=4"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("4 passed"), "{}", text);
+}
@@ -0,0 +1,42 @@
+// the module doesn't compile if any of the warnings is left or if the attributes are not removed
+#![deny(deprecated)]
+
+#[printer::printer]
+mod shadows {
+    #[before_each]
+    fn setup() {
+        let value = 1;
+        #[printer::allow(shadow)]
+        let unused = 2;
+    }
+
+    #[before_each]
+    #[printer::allow(shadow)]
+    fn deploy() {
+        let address = 3;
+    }
+
+    #[after_each]
+    fn teardown() {
+        println!("{}", value);
+    }
+
+    #[test]
+    fn it_uses_the_hooks() {
+        assert_eq!(value, 1);
+    }
+
+    #[test]
+    #[printer::allow(shadow)]
+    fn it_shadows() {
+        let address = 4;
+        assert_eq!(address, 4);
+    }
+
+    #[test]
+    fn it_shadows_a_binding() {
+        #[printer::allow(shadow)]
+        let value = 5;
+        assert_eq!(value, 5);
+    }
+}
@@ -0,0 +1,54 @@
+mod common;
+
+#[printer::printer]
+mod slow {
+    use std::time::Duration;
+
+    #[before_each]
+    fn setup() {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    #[slow("10ms")]
+    #[test]
+    fn sleepy_works() {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    #[slow("10s")]
+    #[test]
+    fn quick_works() {}
+}
+
+#[test]
+fn slow_tests_are_reported() {
+    let output = common::run_tests("slow::", &[], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(
+        text.contains("warning: test `sleepy_works` is slow: its body took"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("(threshold: 10ms, before hooks: "),
+        "{}",
+        text
+    );
+    assert!(!text.contains("`quick_works` is slow"), "{}", text);
+}
+
+#[test]
+fn slow_tests_fail_with_fail_slow() {
+    let output = common::run_tests("slow::", &[], &[("PRINTER_FAIL_SLOW", "1")]);
+    let text = common::output_text(&output);
+
+    assert!(!output.status.success(), "{}", text);
+    assert!(
+        text.contains("test slow::sleepy_works ... FAILED"),
+        "{}",
+        text
+    );
+    assert!(text.contains("test slow::quick_works ... ok"), "{}", text);
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "reporting")]
+
+mod common;
+
+#[printer::printer]
+mod spec {
+    #[before_each]
+    fn deploy() {
+        let deployed = true;
+    }
+
+    #[test]
+    fn a_works() {
+        assert!(deployed);
+    }
+
+    #[test]
+    #[ignore]
+    fn b_ignored_works() {
+        assert!(deployed);
+    }
+
+    #[test]
+    fn c_upgrade_works() {
+        if deployed {
+            printer::skip!("no node");
+        }
+    }
+
+    #[todo]
+    fn d_migrate_works() {}
+}
+
+#[test]
+fn spec_reports_the_module_once_its_tests_finish() {
+    let output = common::run_tests("spec::", &[], &[("PRINTER_REPORTER", "spec")]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(
+        text.contains("  spec\n    spec\n      ✓ a works ("),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(
+            "      - c upgrade works (skipped: no node)\n      - d migrate works (pending: todo)\n"
+        ),
+        "{}",
+        text
+    );
+    assert!(text.contains("  1 passing, 2 pending\n"), "{}", text);
+    assert!(!text.contains("b ignored works"), "{}", text);
+}
+
+#[test]
+fn spec_reports_the_ignored_tests_that_run() {
+    let output = common::run_tests(
+        "spec::",
+        &["--include-ignored"],
+        &[("PRINTER_REPORTER", "spec")],
+    );
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("      ✓ b ignored works ("), "{}", text);
+    assert!(text.contains("  2 passing, 2 pending\n"), "{}", text);
+}
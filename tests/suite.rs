@@ -0,0 +1,101 @@
+mod common;
+
+#[printer::printer]
+mod summary {
+    #[test]
+    fn first_works() {}
+
+    #[test]
+    #[ignore]
+    fn ignored_works() {}
+
+    #[todo]
+    fn pending_works() {}
+
+    #[test]
+    fn second_works() {}
+}
+
+#[test]
+fn summary_is_printed_with_ignored_tests() {
+    let output = common::run_tests("summary::", &[], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("pending tests in"), "{}", text);
+    assert!(text.contains("  - pending_works: todo"), "{}", text);
+}
+
+#[test]
+fn summary_is_printed_when_ignored_tests_run() {
+    let output = common::run_tests("summary::", &["--include-ignored"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(
+        text.contains("test summary::ignored_works ... ok"),
+        "{}",
+        text
+    );
+    assert!(text.contains("pending tests in"), "{}", text);
+}
+
+#[printer::printer]
+mod all_pending {
+    #[todo]
+    fn first_works();
+
+    #[skip("blocked on node upgrade")]
+    #[test]
+    fn second_works() {}
+}
+
+#[test]
+fn summary_is_printed_when_all_tests_are_pending() {
+    let output = common::run_tests("all_pending::", &[], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("pending tests in"), "{}", text);
+    assert!(text.contains("  - first_works: todo"), "{}", text);
+    assert!(
+        text.contains("  - second_works: blocked on node upgrade"),
+        "{}",
+        text
+    );
+}
+
+#[printer::printer(bail)]
+mod bail {
+    #[test]
+    #[ignore]
+    fn a_returns_early() -> Result<(), String> {
+        let deployed: Result<(), String> = Err("not deployed".to_string());
+        deployed?;
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn b_works() -> impl std::process::Termination {
+        Ok::<(), String>(())
+    }
+}
+
+#[test]
+fn bail_skips_after_error_returned_early() {
+    let output = common::run_tests("bail::", &["--ignored", "--test-threads=1"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(!output.status.success(), "{}", text);
+    assert!(
+        text.contains("test bail::a_returns_early ... FAILED"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("b_works: skipped after earlier failure in a_returns_early"),
+        "{}",
+        text
+    );
+}
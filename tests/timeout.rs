@@ -0,0 +1,86 @@
+mod common;
+
+#[printer::printer]
+mod timeouts {
+    use std::time::Duration;
+
+    #[before_each]
+    fn setup() {
+        std::thread::sleep(Duration::from_millis(200));
+    }
+
+    #[after_each]
+    fn teardown() {
+        assert_eq!(answer, 42);
+    }
+
+    #[test]
+    #[timeout("5s")]
+    fn body_bindings_reach_after_each() {
+        let answer = 42;
+    }
+
+    #[test]
+    #[timeout("10ms")]
+    #[should_panic(expected = "test `slow_hook_times_out` timed out after")]
+    fn slow_hook_times_out() {
+        let answer = 42;
+    }
+}
+
+#[printer::printer]
+mod async_timeouts {
+    use std::time::Duration;
+
+    #[before_each]
+    fn setup() {
+        printer::tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    #[printer::tokio::test(crate = "printer::tokio")]
+    #[timeout("10ms")]
+    #[should_panic(expected = "test `slow_hook_times_out` timed out after")]
+    async fn slow_hook_times_out() {}
+}
+
+#[printer::printer]
+mod teardowns {
+    use std::time::Duration;
+
+    #[after_each]
+    fn teardown() {
+        println!("teardown after {}", std::thread::current().name().unwrap());
+    }
+
+    #[test]
+    #[timeout("10ms")]
+    #[should_panic(expected = "test `sync_times_out` timed out after")]
+    fn sync_times_out() {
+        std::thread::sleep(Duration::from_secs(5));
+    }
+
+    #[printer::tokio::test(crate = "printer::tokio")]
+    #[timeout("10ms")]
+    #[should_panic(expected = "test `async_times_out` timed out after")]
+    async fn async_times_out() {
+        printer::tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+#[test]
+fn after_each_runs_when_the_test_times_out() {
+    let output = common::run_tests("teardowns::", &["--nocapture"], &[]);
+    let text = common::output_text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(
+        text.contains("teardown after teardowns::sync_times_out"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("teardown after teardowns::async_times_out"),
+        "{}",
+        text
+    );
+}